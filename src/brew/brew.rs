@@ -1,7 +1,23 @@
+use std::env;
+use std::fs;
 use std::io::{self};
-use std::process::{Command, exit};
+use std::path::{Path, PathBuf};
+use std::process::Command;
 use termion::color;
 
+use crate::system::{environment, path};
+
+/// URL of the official Homebrew installer.
+pub const INSTALLER_URL: &str =
+    "https://raw.githubusercontent.com/Homebrew/install/HEAD/install.sh";
+
+/// Where the Homebrew installer puts `brew` on Apple silicon, Intel Macs and Linux.
+const BREW_LOCATIONS: [&str; 3] = [
+    "/opt/homebrew/bin/brew",
+    "/usr/local/bin/brew",
+    "/home/linuxbrew/.linuxbrew/bin/brew",
+];
+
 /// Checks if Homebrew is installed.
 fn is_homebrew_installed() -> bool {
     Command::new("brew").arg("--version").output().is_ok()
@@ -31,25 +47,145 @@ fn update_homebrew() -> Result<(), io::Error> {
     }
 }
 
+/// Computes the SHA-256 checksum of `path` with `sha256sum` or `shasum`.
+pub fn sha256(path: &Path) -> Result<String, io::Error> {
+    let output = Command::new("sha256sum").arg(path).output().or_else(|_| {
+        Command::new("shasum")
+            .args(["-a", "256"])
+            .arg(path)
+            .output()
+    })?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    match stdout.split_whitespace().next() {
+        Some(checksum) if output.status.success() => Ok(checksum.to_lowercase()),
+        _ => Err(io::Error::other(format!(
+            "Could not compute the checksum of {}",
+            path.display()
+        ))),
+    }
+}
+
+/// Checks that the file at `path` has the SHA-256 checksum `expected`.
+///
+/// # Errors
+///
+/// This function returns an error if the checksum differs or cannot be computed.
+pub fn verify_checksum(path: &Path, expected: &str) -> Result<(), io::Error> {
+    let actual = sha256(path)?;
+    if actual == expected.trim().to_lowercase() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "Checksum mismatch for {}: expected {}, got {}",
+            path.display(),
+            expected.trim(),
+            actual
+        )))
+    }
+}
+
+/// Finds the `brew` binary on `PATH` or in the installer's default locations.
+fn find_brew() -> Option<PathBuf> {
+    path::which("brew").or_else(|| {
+        BREW_LOCATIONS
+            .iter()
+            .map(PathBuf::from)
+            .find(|location| location.exists())
+    })
+}
+
+/// Downloads the official installer and runs it non-interactively.
+///
+/// When `expected_sha256` is set, the downloaded script must match it before it runs.
+///
+/// # Errors
+///
+/// This function returns an error if the download, checksum or installation fails.
+pub fn install_homebrew_from_script(
+    url: &str,
+    expected_sha256: Option<&str>,
+) -> Result<(), io::Error> {
+    let script = env::temp_dir().join(format!(
+        "instant-dev-brew-install-{}.sh",
+        std::process::id()
+    ));
+
+    let download = Command::new("curl")
+        .args(["-fsSL", url, "-o"])
+        .arg(&script)
+        .status()?;
+    if !download.success() {
+        return Err(io::Error::other(format!("Failed to download {}", url)));
+    }
+
+    let result = match expected_sha256 {
+        Some(expected) => verify_checksum(&script, expected),
+        None => {
+            println!("No [homebrew] installer_sha256 configured; skipping the checksum check.");
+            Ok(())
+        }
+    }
+    .and_then(|()| {
+        let status = Command::new("/bin/bash")
+            .arg(&script)
+            .env("NONINTERACTIVE", "1")
+            .status()?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!(
+                "Homebrew installer exited with {}",
+                status
+            )))
+        }
+    });
+
+    let _ = fs::remove_file(&script);
+    result
+}
+
+/// Adds `brew shellenv` to the managed shell environment and to this process's `PATH`.
+fn configure_brew_environment(brew: &Path) -> Result<(), io::Error> {
+    environment::add_eval(&format!("{} shellenv", brew.display()))?;
+
+    if let Some(bin) = brew.parent() {
+        let mut paths = vec![bin.to_path_buf()];
+        paths.extend(env::split_paths(&env::var_os("PATH").unwrap_or_default()));
+        env::set_var("PATH", env::join_paths(paths).map_err(io::Error::other)?);
+    }
+    Ok(())
+}
+
 /// Installs Homebrew if it is not already installed, and updates it if it is.
 ///
+/// Without `install`, a missing Homebrew prints the install command and returns a
+/// `NotFound` error.
+/// With it, the official installer is downloaded, checked against `installer_sha256`
+/// when one is pinned, and run non-interactively before the run continues.
+///
 /// # Errors
 ///
 /// This function returns an error if there are any issues with the installation or update process.
 ///
 /// # Example
 ///
-/// ```
+/// ```no_run
 /// use std::io;
-/// use my_crate::install_homebrew;
+/// use instant_dev::brew::brew::{install_homebrew, INSTALLER_URL};
 ///
 /// fn main() -> Result<(), io::Error> {
-///     install_homebrew()?;
+///     install_homebrew(true, INSTALLER_URL, None)?;
 ///     Ok(())
 /// }
 /// ```
-pub fn install_homebrew() -> Result<(), io::Error> {
-    println!("\n{}--- Homebrew Installation and Configuration ---{}",
+pub fn install_homebrew(
+    install: bool,
+    installer_url: &str,
+    installer_sha256: Option<&str>,
+) -> Result<(), io::Error> {
+    println!(
+        "\n{}--- Homebrew Installation and Configuration ---{}",
         color::Fg(color::Yellow),
         color::Fg(color::Reset)
     );
@@ -57,21 +193,34 @@ pub fn install_homebrew() -> Result<(), io::Error> {
         println!("Homebrew is already installed, checking for updates...");
         // Perform any necessary post-installation tasks
         update_homebrew()?;
+    } else if install {
+        println!("Homebrew is not installed. Running the official installer...");
+        install_homebrew_from_script(installer_url, installer_sha256)?;
+        let brew = find_brew().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "brew not found after installation")
+        })?;
+        configure_brew_environment(&brew)?;
+        println!("Homebrew installed at {}.", brew.display());
     } else {
         println!("\n{}-------------------------------------------------------------------------------------------------",
             color::Fg(color::Green)
         );
         println!("Homebrew is not installed.");
-        println!("Please install Homebrew using the following command:");
-        println!("{}/bin/bash -c \"$(curl -fsSL https://raw.githubusercontent.com/Homebrew/install/HEAD/install.sh)\"{}",
+        println!("Re-run with --install-brew to install it automatically, or install it using the following command:");
+        println!(
+            "{}/bin/bash -c \"$(curl -fsSL {})\"{}",
             color::Fg(color::Cyan),
+            INSTALLER_URL,
             color::Fg(color::Green)
         );
         println!("Then re-run this application.");
         println!("-------------------------------------------------------------------------------------------------{}",
             color::Fg(color::Reset)
         );
-        exit(0); // Exit the application with an error code
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "Homebrew is not installed",
+        ));
     }
 
     println!("Homebrew installation and configuration completed successfully.");
@@ -0,0 +1,314 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use toml::Value;
+
+use crate::config::{manifest, remote};
+
+/// The entries of a Homebrew Bundle `Brewfile`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Bundle {
+    pub taps: Vec<String>,
+    pub brews: Vec<String>,
+    pub casks: Vec<String>,
+    /// Mac App Store apps by name, with their App Store id.
+    pub mas: BTreeMap<String, i64>,
+    /// Lines that could not be converted, such as `vscode` or `whalebrew` entries.
+    pub skipped: Vec<String>,
+}
+
+/// The first double-quoted string in `args`, e.g. `jq` in `"jq", link: false`.
+fn first_string(args: &str) -> Option<&str> {
+    let rest = args.trim().strip_prefix('"')?;
+    rest.split('"').next()
+}
+
+/// Parse a `Brewfile`.
+///
+/// `brew`, `cask`, `tap` and `mas` lines are converted. Options after the name
+/// (like `link: false` or a custom tap URL) are dropped, and other lines are kept
+/// in [`Bundle::skipped`].
+pub fn parse_brewfile(content: &str) -> Bundle {
+    let mut bundle = Bundle::default();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (directive, args) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let name = first_string(args).map(str::to_string);
+        match (directive, name) {
+            ("tap", Some(name)) => bundle.taps.push(name),
+            ("brew", Some(name)) => bundle.brews.push(name),
+            ("cask", Some(name)) => bundle.casks.push(name),
+            ("mas", Some(name)) => {
+                let id = args
+                    .split_once("id:")
+                    .and_then(|(_, id)| id.trim().trim_end_matches(',').parse().ok());
+                match id {
+                    Some(id) => {
+                        bundle.mas.insert(name, id);
+                    }
+                    None => bundle.skipped.push(line.to_string()),
+                }
+            }
+            _ => bundle.skipped.push(line.to_string()),
+        }
+    }
+    bundle
+}
+
+/// Quote a string for a `Brewfile` or TOML file.
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Read the Homebrew entries from the config: `taps`, `packages`, `casks` and `[mas]`.
+pub fn bundle_from_config(config: &Value) -> Bundle {
+    Bundle {
+        taps: manifest::strings(config, &["taps"]),
+        brews: manifest::strings(config, &["packages"]),
+        casks: manifest::strings(config, &["casks"]),
+        mas: manifest::table(config, &["mas"])
+            .map(|apps| {
+                apps.iter()
+                    .filter_map(|(name, id)| id.as_integer().map(|id| (name.clone(), id)))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        skipped: Vec::new(),
+    }
+}
+
+/// Render a `Brewfile` for `bundle`.
+pub fn render_brewfile(bundle: &Bundle) -> String {
+    let mut brewfile = String::from("# Generated by instant-dev from packages.toml.\n");
+    let mut section = |directive: &str, names: &[String]| {
+        if names.is_empty() {
+            return;
+        }
+        brewfile.push('\n');
+        for name in names {
+            brewfile.push_str(&format!("{} {}\n", directive, quote(name)));
+        }
+    };
+    section("tap", &bundle.taps);
+    section("brew", &bundle.brews);
+    section("cask", &bundle.casks);
+
+    if !bundle.mas.is_empty() {
+        brewfile.push('\n');
+        for (name, id) in &bundle.mas {
+            brewfile.push_str(&format!("mas {}, id: {}\n", quote(name), id));
+        }
+    }
+    brewfile
+}
+
+/// Render a top-level TOML array, wrapping it when it gets long.
+fn render_array(key: &str, values: &[String]) -> String {
+    let items: Vec<String> = values.iter().map(|value| quote(value)).collect();
+    let line = format!("{} = [{}]", key, items.join(", "));
+    if line.len() <= 80 {
+        return line;
+    }
+    format!("{} = [\n  {},\n]", key, items.join(",\n  "))
+}
+
+/// Line and byte offset just past the `]` closing the array that opens on
+/// `lines[start]`.
+///
+/// Brackets inside strings and comments are ignored, so a trailing comment such
+/// as `packages = ["go"] # core` does not hide the end of the array.
+fn array_end(lines: &[&str], start: usize) -> Option<(usize, usize)> {
+    let mut depth = 0;
+    for (index, line) in lines.iter().enumerate().skip(start) {
+        let skip = if index == start { line.find('=')? } else { 0 };
+        let mut chars = line.char_indices().skip_while(|&(offset, _)| offset < skip);
+        let mut quote = None;
+        while let Some((offset, c)) = chars.next() {
+            match (quote, c) {
+                (Some('"'), '\\') => {
+                    chars.next();
+                }
+                (Some(open), c) if c == open => quote = None,
+                (Some(_), _) => {}
+                (None, '"' | '\'') => quote = Some(c),
+                (None, '#') => break,
+                (None, '[') => depth += 1,
+                (None, ']') => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some((index, offset + 1));
+                    }
+                }
+                (None, _) => {}
+            }
+        }
+    }
+    None
+}
+
+/// Add `values` to the top-level array `key` in the TOML text `content`.
+///
+/// Existing values keep their order and new ones are appended. The rest of the
+/// file, including comments, is left untouched.
+pub fn merge_array(content: &str, key: &str, values: &[String]) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let first_table = lines
+        .iter()
+        .position(|line| line.trim_start().starts_with('['))
+        .unwrap_or(lines.len());
+    let start = lines[..first_table].iter().position(|line| {
+        line.split_once('=')
+            .is_some_and(|(name, _)| name.trim() == key)
+    });
+
+    let mut trailing = "";
+    let (start, end, mut merged) = match start {
+        Some(start) => {
+            let (end, column) = array_end(&lines, start).unwrap_or((start, lines[start].len()));
+            trailing = &lines[end][column..];
+            let existing = lines[start..end]
+                .iter()
+                .copied()
+                .chain([&lines[end][..column]])
+                .collect::<Vec<_>>()
+                .join("\n");
+            let existing = toml::from_str::<Value>(&existing)
+                .map(|value| manifest::strings(&value, &[key]))
+                .unwrap_or_default();
+            (start, end + 1, existing)
+        }
+        None => {
+            // Insert above the first table and the comments describing it.
+            let mut index = first_table;
+            while index > 0 && lines[index - 1].trim_start().starts_with('#') {
+                index -= 1;
+            }
+            (index, index, Vec::new())
+        }
+    };
+
+    let before = merged.len();
+    for value in values {
+        if !merged.contains(value) {
+            merged.push(value.clone());
+        }
+    }
+    if merged.len() == before {
+        return content.to_string();
+    }
+
+    let mut rendered = vec![render_array(key, &merged) + trailing];
+    if start == end {
+        if start > 0 && !lines[start - 1].trim().is_empty() {
+            rendered.insert(0, String::new());
+        }
+        if start < lines.len() {
+            rendered.push(String::new());
+        }
+    }
+    let mut output: Vec<String> = lines[..start].iter().map(|line| line.to_string()).collect();
+    output.extend(rendered);
+    output.extend(lines[end..].iter().map(|line| line.to_string()));
+    output.join("\n") + "\n"
+}
+
+/// Add the Mac App Store `apps` to the `[mas]` table in the TOML text `content`.
+pub fn merge_mas(content: &str, apps: &BTreeMap<String, i64>) -> String {
+    let existing = toml::from_str::<Value>(content).unwrap_or(Value::Table(Default::default()));
+    let existing = manifest::table(&existing, &["mas"]);
+    let entries: Vec<String> = apps
+        .iter()
+        .filter(|(name, _)| existing.is_none_or(|mas| !mas.contains_key(*name)))
+        .map(|(name, id)| format!("{} = {}", quote(name), id))
+        .collect();
+    if entries.is_empty() {
+        return content.to_string();
+    }
+
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    match lines.iter().position(|line| line.trim() == "[mas]") {
+        Some(header) => {
+            for (offset, entry) in entries.into_iter().enumerate() {
+                lines.insert(header + 1 + offset, entry);
+            }
+        }
+        None => {
+            if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.push("# Mac App Store apps installed with `mas`, by App Store id.".to_string());
+            lines.push("[mas]".to_string());
+            lines.extend(entries);
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Merge `bundle` into the TOML text `content` of packages.toml.
+pub fn merge_bundle(content: &str, bundle: &Bundle) -> String {
+    let content = merge_array(content, "taps", &bundle.taps);
+    let content = merge_array(&content, "packages", &bundle.brews);
+    let content = merge_array(&content, "casks", &bundle.casks);
+    merge_mas(&content, &bundle.mas)
+}
+
+/// Import a `Brewfile` into packages.toml, for `instant-dev import brewfile <path>`.
+pub fn import_brewfile(brewfile: &Path) -> io::Result<()> {
+    let bundle = parse_brewfile(&fs::read_to_string(brewfile)?);
+    for line in &bundle.skipped {
+        eprintln!("Skipping unsupported Brewfile line: {}", line);
+    }
+
+    let config_path = manifest::config_path();
+    if config_path.starts_with(remote::cache_root()) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{} is a remote config; import into a local packages.toml instead",
+                config_path.display()
+            ),
+        ));
+    }
+    let content = fs::read_to_string(&config_path).unwrap_or_default();
+    let merged = merge_bundle(&content, &bundle);
+    if merged == content {
+        println!(
+            "{} already has everything in {}.",
+            config_path.display(),
+            brewfile.display()
+        );
+        return Ok(());
+    }
+
+    fs::create_dir_all(manifest::config_dir())?;
+    fs::write(&config_path, merged)?;
+    println!(
+        "Imported {} taps, {} packages, {} casks and {} Mac App Store apps into {}.",
+        bundle.taps.len(),
+        bundle.brews.len(),
+        bundle.casks.len(),
+        bundle.mas.len(),
+        config_path.display()
+    );
+    Ok(())
+}
+
+/// Write a `Brewfile` from packages.toml, for `instant-dev export brewfile [path]`.
+pub fn export_brewfile(brewfile: &Path) -> io::Result<()> {
+    let config = manifest::load().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No usable {}", manifest::config_path().display()),
+        )
+    })?;
+
+    fs::write(brewfile, render_brewfile(&bundle_from_config(&config)))?;
+    println!("Wrote {}.", brewfile.display());
+    Ok(())
+}
@@ -1,139 +1,154 @@
-use std::{fs, process::Command};
+use std::process::Command;
 use termion::color;
 use toml::Value;
 
-/// Retrieves a list of installed packages using Homebrew.
-fn get_installed_packages() -> Vec<String> {
-    let output = Command::new("brew")
-        .args(&["list"])
-        .output()
-        .expect("Failed to get list of installed packages.");
+use crate::brew::bundle;
+use crate::config::manifest;
+use crate::system::os::Os;
+use crate::system::package_manager::{self, PackageManager};
+use crate::system::{exec, path};
 
-    if output.status.success() {
-        String::from_utf8_lossy(&output.stdout)
-            .lines()
-            .map(|line| line.trim().to_string())
-            .collect()
-    } else {
-        eprintln!("Failed to get list of installed packages.");
-        Vec::new()
-    }
+/// Installs a package, reporting the result.
+fn install_package(manager: &dyn PackageManager, package: &str) {
+    exec::install(package, || manager.install(package));
 }
 
-/// Retrieves a list of installed casks using Homebrew.
-fn get_installed_casks() -> Vec<String> {
-    let output = Command::new("brew")
-        .args(&["list", "--cask"])
-        .output()
-        .expect("Failed to get list of installed casks.");
-
-    if output.status.success() {
-        String::from_utf8_lossy(&output.stdout)
-            .lines()
-            .map(|line| line.trim().to_string())
-            .collect()
-    } else {
-        eprintln!("Failed to get list of installed casks.");
-        Vec::new()
-    }
+/// Installs a cask, reporting the result.
+fn install_cask(manager: &dyn PackageManager, cask: &str) {
+    exec::install(cask, || manager.install_cask(cask));
 }
 
-/// Installs a package using Homebrew.
-fn install_package(package: &str) {
-    println!("Installing {}...", package);
+/// Adds the taps listed in `taps`, before installing packages from them.
+fn add_taps(manager: &dyn PackageManager, toml: &Value) {
+    let taps = manifest::strings(toml, &["taps"]);
+    if taps.is_empty() {
+        return;
+    }
+    if !manager.supports_casks() {
+        println!("Taps are only added with Homebrew. Skipping...");
+        return;
+    }
 
-    let output = Command::new("brew")
-        .args(&["install", package])
-        .output()
-        .expect("Failed to install package.");
+    let installed_taps = manager.installed_taps();
+    for tap in taps {
+        if installed_taps.contains(&tap) {
+            println!("{} is already tapped.", tap);
+            continue;
+        }
 
-    if output.status.success() {
-        println!("{} installed successfully.", package);
-    } else {
-        eprintln!("Failed to install {}.", package);
+        if exec::is_dry_run() {
+            println!("Would tap {}.", tap);
+            continue;
+        }
+        println!("Tapping {}...", tap);
+        match manager.tap(&tap) {
+            Ok(()) => println!("{} tapped successfully.", tap),
+            Err(e) => eprintln!("Failed to tap {}: {}", tap, e),
+        }
     }
 }
 
-/// Installs a cask using Homebrew.
-fn install_cask(cask: &str) {
-    println!("Installing {}...", cask);
+/// Installs the Mac App Store apps listed under `[mas]` with the `mas` CLI.
+fn install_mas_apps(toml: &Value) {
+    let apps = bundle::bundle_from_config(toml).mas;
+    if apps.is_empty() {
+        return;
+    }
+    if Os::current() != Os::MacOs || !path::has_binary("mas") {
+        println!("Mac App Store apps need macOS and the `mas` package. Skipping...");
+        return;
+    }
 
-    let output = Command::new("brew")
-        .args(&["install", "--cask", cask])
+    let installed = Command::new("mas")
+        .arg("list")
         .output()
-        .expect("Failed to install cask.");
+        .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+        .unwrap_or_default();
+    let installed_ids: Vec<&str> = installed
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .collect();
+
+    for (name, id) in apps {
+        if installed_ids.contains(&id.to_string().as_str()) {
+            println!("{} is already installed.", name);
+            continue;
+        }
 
-    if output.status.success() {
-        println!("{} installed successfully.", cask);
-    } else {
-        eprintln!("Failed to install {}.", cask);
+        exec::install(&name, || {
+            exec::run(Command::new("mas").args(["install", &id.to_string()]))
+        });
     }
 }
 
-/// Installs packages and casks listed in the `packages.toml` file using Homebrew.
-pub fn packages() {
+/// Installs taps, packages, casks and Mac App Store apps listed in the `packages.toml` file.
+///
+/// Packages go through `manager`, with names translated by `[package_map]`.
+/// Taps and casks are only installed when the manager is Homebrew.
+pub fn packages(manager: &dyn PackageManager, toml: Option<&Value>) {
     println!(
-        "\n{} --- Installing Packages with Homebrew --- {}",
+        "\n{} --- Installing Packages with {} --- {}",
         color::Fg(color::Yellow),
+        manager.name(),
         color::Fg(color::Reset)
     );
 
-    // Get the username using `echo $USER` command
-    let username_output = Command::new("sh")
-        .arg("-c")
-        .arg("echo $USER")
-        .output()
-        .expect("Failed to get username.");
-
-    let username = String::from_utf8_lossy(&username_output.stdout)
-        .trim()
-        .to_string();
-
-    let toml_path = format!("/Users/{}/.config/instant-dev/packages.toml", username);
-
-    if !fs::metadata(&toml_path).is_ok() {
+    let Some(toml) = toml else {
         println!("No packages.toml file found. Skipping...");
         return;
-    }
+    };
 
-    let toml_content =
-        fs::read_to_string(&toml_path).expect("Failed to read packages.toml");
+    add_taps(manager, toml);
 
-    let toml: Value = toml::from_str(&toml_content).expect("Failed to parse TOML");
-
-    let installed_packages = get_installed_packages();
+    let installed_packages = manager.installed();
 
     if let Some(packages) = toml.get("packages").and_then(Value::as_array) {
         // Check and install packages
         for package in packages {
-            if let Some(package_name) = package.as_str() {
-                let package_name = package_name.to_string();
-                if !installed_packages.contains(&package_name) {
-                    install_package(&package_name);
-                } else {
-                    println!("{} is already installed.", package_name);
-                }
+            let Some(tool) = package.as_str() else {
+                continue;
+            };
+            let Some(package_name) = package_manager::resolve_package(toml, tool, manager.name())
+            else {
+                println!("{} is skipped for {}.", tool, manager.name());
+                continue;
+            };
+            if !installed_packages.contains(&package_name) {
+                install_package(manager, &package_name);
+            } else {
+                println!("{} is already installed.", package_name);
             }
         }
     } else {
         eprintln!("Invalid or missing 'packages' array in packages.toml");
     }
 
-    let installed_casks = get_installed_casks();
-
-    if let Some(casks) = toml.get("casks").and_then(Value::as_array) {
-        // Check and install casks
-        for cask in casks {
-            if let Some(cask_name) = cask.as_str() {
-                let cask_name = cask_name.to_string();
-                if !installed_casks.contains(&cask_name) {
-                    install_cask(&cask_name);
-                } else {
-                    println!("{} is already installed.", cask_name);
-                }
+    install_casks(manager, toml);
+    install_mas_apps(toml);
+}
+
+/// Installs the casks listed in `casks`, when the manager supports them.
+fn install_casks(manager: &dyn PackageManager, toml: &Value) {
+    let Some(casks) = toml.get("casks").and_then(Value::as_array) else {
+        eprintln!("Invalid or missing 'casks' array in packages.toml");
+        return;
+    };
+    if !manager.supports_casks() {
+        println!("Casks are only installed with Homebrew. Skipping...");
+        return;
+    }
+
+    let installed_casks = manager.installed_casks();
+
+    // Check and install casks
+    for cask in casks {
+        if let Some(cask_name) = cask.as_str() {
+            let cask_name = cask_name.to_string();
+            if !installed_casks.contains(&cask_name) {
+                install_cask(manager, &cask_name);
+            } else {
+                println!("{} is already installed.", cask_name);
             }
         }
-    } else {
-        eprintln!("Invalid or missing 'casks' array in packages.toml");
     }
 }
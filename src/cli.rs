@@ -0,0 +1,187 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::config::profiles;
+
+/// What instant-dev was asked to do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Run every setup stage.
+    Setup,
+    /// Remove the shell environment hooks added by instant-dev.
+    Uninstall,
+    /// Pull the dotfiles repo and refresh its links.
+    DotfilesPull,
+    /// Merge a Homebrew Bundle `Brewfile` into packages.toml.
+    ImportBrewfile(PathBuf),
+    /// Write a `Brewfile` from packages.toml.
+    ExportBrewfile(PathBuf),
+    /// Print a packages.toml for this machine, or with `diff`, what the config lacks.
+    Snapshot { diff: bool },
+    /// Print packages.toml, or with `resolved`, the merged config and where each
+    /// entry comes from.
+    ConfigShow { resolved: bool },
+    /// List the profiles and what each adds.
+    Profiles,
+    /// Save the default profiles; an empty list clears them.
+    UseProfiles(Vec<String>),
+    /// Print the usage text.
+    Help,
+}
+
+/// Parsed command-line arguments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cli {
+    pub command: Command,
+    pub verbose: bool,
+    /// Install Homebrew with the official installer when it is missing.
+    pub install_brew: bool,
+    /// Report the changes setup would make without making them.
+    pub dry_run: bool,
+    /// Config file, git repo or URL to use instead of `~/.config/instant-dev/packages.toml`.
+    pub config: Option<String>,
+    /// Profiles chosen with `--profile`, instead of the saved default.
+    pub profiles: Option<Vec<String>>,
+}
+
+/// An argument that could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CliError(pub String);
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Usage text printed by `--help` and on invalid arguments.
+pub const USAGE: &str = "\
+Usage: instant-dev [OPTIONS] [COMMAND]
+
+Commands:
+  setup            Run every setup stage (default)
+  uninstall        Remove the shell environment hooks added by instant-dev
+  dotfiles pull    Pull the dotfiles repo and refresh its links
+  import brewfile <path>
+                   Merge a Brewfile's taps, brews, casks and mas apps into packages.toml
+  export brewfile [path]
+                   Write a Brewfile from packages.toml (default: ./Brewfile)
+  snapshot [--diff]
+                   Print a packages.toml for this machine, or with --diff,
+                   what it has that the current config lacks
+  config show [--resolved]
+                   Print packages.toml, or with --resolved, the config merged with
+                   its `extends` and packages.local.toml, with each entry's source
+  profiles         List the [profiles.<name>] sections and what each adds
+  profiles use [names]
+                   Save the default profiles, e.g. `backend,data`; no names clears them
+
+Options:
+  -v, --verbose       Print verbose output
+      --install-brew  Install Homebrew non-interactively if it is missing
+  -n, --dry-run       Show the packages setup would install without installing them;
+                      stages that cannot report their changes are skipped, and hooks
+                      run with INSTANT_DEV_DRY_RUN=1
+      --config <location>
+                      Use this config instead of ~/.config/instant-dev/packages.toml:
+                      a path, an https URL, or git+ssh://host/repo.git#path/packages.toml
+                      (add ?ref=<rev> before # to pin a branch, tag or commit)
+      --profile <names>
+                      Add these [profiles.<name>] sections, e.g. `backend,data`,
+                      instead of the saved default
+  -h, --help          Print this help";
+
+impl Cli {
+    /// Parse the arguments that follow the program name.
+    pub fn parse<I, S>(args: I) -> Result<Cli, CliError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut cli = Cli {
+            command: Command::Setup,
+            verbose: false,
+            install_brew: false,
+            dry_run: false,
+            config: None,
+            profiles: None,
+        };
+        let mut command = None;
+
+        let mut args = args.into_iter().peekable();
+        while let Some(arg) = args.next() {
+            match arg.as_ref() {
+                "-v" | "--verbose" => cli.verbose = true,
+                "--install-brew" => cli.install_brew = true,
+                "-n" | "--dry-run" => cli.dry_run = true,
+                "--config" => match args.next() {
+                    Some(location) => cli.config = Some(location.as_ref().to_string()),
+                    None => return Err(CliError("Expected `--config <location>`".to_string())),
+                },
+                "--profile" => match args.next() {
+                    Some(names) => cli.profiles = Some(profiles::parse_list(names.as_ref())),
+                    None => return Err(CliError("Expected `--profile <names>`".to_string())),
+                },
+                "-h" | "--help" | "help" => command = Some(Command::Help),
+                "setup" if command.is_none() => command = Some(Command::Setup),
+                "uninstall" if command.is_none() => command = Some(Command::Uninstall),
+                "dotfiles" if command.is_none() => match args.next() {
+                    Some(sub) if sub.as_ref() == "pull" => command = Some(Command::DotfilesPull),
+                    _ => return Err(CliError("Expected `dotfiles pull`".to_string())),
+                },
+                "import" if command.is_none() => {
+                    let path = match (args.next(), args.next()) {
+                        (Some(kind), Some(path)) if kind.as_ref() == "brewfile" => path,
+                        _ => return Err(CliError("Expected `import brewfile <path>`".to_string())),
+                    };
+                    command = Some(Command::ImportBrewfile(PathBuf::from(path.as_ref())));
+                }
+                "export" if command.is_none() => {
+                    if args.next().is_none_or(|kind| kind.as_ref() != "brewfile") {
+                        return Err(CliError("Expected `export brewfile [path]`".to_string()));
+                    }
+                    let path = match args.peek() {
+                        Some(path) if !path.as_ref().starts_with('-') => args.next(),
+                        _ => None,
+                    };
+                    let path = path.map_or_else(
+                        || PathBuf::from("Brewfile"),
+                        |path| PathBuf::from(path.as_ref()),
+                    );
+                    command = Some(Command::ExportBrewfile(path));
+                }
+                "snapshot" if command.is_none() => {
+                    let diff = args.next_if(|arg| arg.as_ref() == "--diff").is_some();
+                    command = Some(Command::Snapshot { diff });
+                }
+                "config" if command.is_none() => {
+                    if args.next().is_none_or(|sub| sub.as_ref() != "show") {
+                        return Err(CliError("Expected `config show [--resolved]`".to_string()));
+                    }
+                    let resolved = args.next_if(|arg| arg.as_ref() == "--resolved").is_some();
+                    command = Some(Command::ConfigShow { resolved });
+                }
+                "profiles" if command.is_none() => {
+                    if args.next_if(|arg| arg.as_ref() == "use").is_some() {
+                        let names = match args.peek() {
+                            Some(names) if !names.as_ref().starts_with('-') => args.next(),
+                            _ => None,
+                        };
+                        let names = names
+                            .map(|names| profiles::parse_list(names.as_ref()))
+                            .unwrap_or_default();
+                        command = Some(Command::UseProfiles(names));
+                    } else {
+                        command = Some(Command::Profiles);
+                    }
+                }
+                other => return Err(CliError(format!("Unexpected argument '{}'", other))),
+            }
+        }
+
+        if let Some(command) = command {
+            cli.command = command;
+        }
+        Ok(cli)
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::process::Stdio;
+use termion::color;
+use toml::Value;
+
+use crate::config::manifest;
+use crate::system::exec;
+use crate::system::os::Os;
+
+/// A tool installed by its own commands, from a `[[custom]]` entry.
+///
+/// ```toml
+/// [[custom]]
+/// name = "acme-cli"
+/// check = "command -v acme"            # exit 0 means already installed
+/// install = "curl -fsSL https://tools.acme.internal/install.sh | sh"
+/// depends_on = ["acme-vpn"]
+/// platforms = ["macos", "linux"]       # defaults to every platform
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomStep {
+    pub name: String,
+    pub check: String,
+    pub install: String,
+    pub depends_on: Vec<String>,
+    /// Platforms the step runs on; empty means every platform.
+    pub platforms: Vec<Os>,
+}
+
+impl CustomStep {
+    /// Read one `[[custom]]` entry.
+    fn from_value(value: &Value) -> Result<CustomStep, String> {
+        let string = |key: &str| value.get(key).and_then(Value::as_str).map(str::to_string);
+        let name = string("name").ok_or("every [[custom]] entry needs a `name`")?;
+        let required = |key: &str| {
+            string(key).ok_or_else(|| format!("[[custom]] {} needs a `{}` command", name, key))
+        };
+
+        let platforms = manifest::strings(value, &["platforms"])
+            .iter()
+            .map(|platform| match Os::from_name(platform) {
+                Os::Other => Err(format!(
+                    "[[custom]] {} has unknown platform '{}'",
+                    name, platform
+                )),
+                os => Ok(os),
+            })
+            .collect::<Result<Vec<Os>, String>>()?;
+
+        Ok(CustomStep {
+            check: required("check")?,
+            install: required("install")?,
+            depends_on: manifest::strings(value, &["depends_on"]),
+            platforms,
+            name,
+        })
+    }
+
+    /// Check if the step runs on `os`.
+    pub fn runs_on(&self, os: Os) -> bool {
+        self.platforms.is_empty() || self.platforms.contains(&os)
+    }
+}
+
+/// Read the `[[custom]]` entries. Each step needs a unique name.
+pub fn steps(config: &Value) -> Result<Vec<CustomStep>, String> {
+    let steps: Vec<CustomStep> = match config.get("custom") {
+        None => return Ok(Vec::new()),
+        Some(Value::Array(entries)) => entries
+            .iter()
+            .map(CustomStep::from_value)
+            .collect::<Result<_, _>>()?,
+        Some(_) => return Err("`custom` must be a list of [[custom]] entries".to_string()),
+    };
+
+    let mut names = BTreeSet::new();
+    if let Some(duplicate) = steps.iter().find(|step| !names.insert(&step.name)) {
+        return Err(format!(
+            "Custom step '{}' is defined more than once",
+            duplicate.name
+        ));
+    }
+    Ok(steps)
+}
+
+/// Order `steps` so each comes after the steps it depends on.
+///
+/// Steps keep their config order otherwise. Unknown dependencies and cycles are
+/// errors.
+pub fn order(steps: &[CustomStep]) -> Result<Vec<&CustomStep>, String> {
+    for step in steps {
+        if let Some(missing) = step
+            .depends_on
+            .iter()
+            .find(|dependency| !steps.iter().any(|step| &step.name == *dependency))
+        {
+            return Err(format!(
+                "{} depends on unknown custom step '{}'",
+                step.name, missing
+            ));
+        }
+    }
+
+    let mut ordered: Vec<&CustomStep> = Vec::new();
+    let mut done = BTreeSet::new();
+    while ordered.len() < steps.len() {
+        let ready = steps.iter().find(|step| {
+            !done.contains(&step.name)
+                && step
+                    .depends_on
+                    .iter()
+                    .all(|dependency| done.contains(dependency))
+        });
+        let Some(step) = ready else {
+            let waiting: Vec<&str> = steps
+                .iter()
+                .filter(|step| !done.contains(&step.name))
+                .map(|step| step.name.as_str())
+                .collect();
+            return Err(format!(
+                "Custom steps depend on each other in a cycle: {}",
+                waiting.join(", ")
+            ));
+        };
+        done.insert(step.name.clone());
+        ordered.push(step);
+    }
+    Ok(ordered)
+}
+
+/// Run the custom steps for `os` from `dir`, in dependency order.
+///
+/// A step is installed when its `check` fails. Steps whose dependencies failed
+/// are skipped. Returns the names of the steps that failed or were skipped.
+pub fn run(steps: &[CustomStep], os: Os, dir: Option<&Path>) -> Result<Vec<String>, String> {
+    let mut failed: Vec<String> = Vec::new();
+
+    for step in order(steps)? {
+        if !step.runs_on(os) {
+            println!("{} is skipped on {}.", step.name, os.name());
+            continue;
+        }
+        if let Some(dependency) = step
+            .depends_on
+            .iter()
+            .find(|dependency| failed.contains(dependency))
+        {
+            eprintln!("Skipping {}: {} was not installed.", step.name, dependency);
+            failed.push(step.name.clone());
+            continue;
+        }
+        if exec::succeeds(&mut exec::shell(&step.check, dir)) {
+            println!("{} is already installed.", step.name);
+            continue;
+        }
+
+        if !exec::install(&step.name, || {
+            exec::run(
+                exec::shell(&step.install, dir)
+                    .stdout(Stdio::inherit())
+                    .stderr(Stdio::inherit()),
+            )
+        }) {
+            failed.push(step.name.clone());
+        }
+    }
+    Ok(failed)
+}
+
+/// Run the `[[custom]]` install steps.
+pub fn configure_custom() {
+    let Some(config) = manifest::load() else {
+        return;
+    };
+    let steps = match steps(&config) {
+        Ok(steps) if steps.is_empty() => return,
+        Ok(steps) => steps,
+        Err(error) => {
+            eprintln!("Invalid [[custom]] configuration: {}", error);
+            return;
+        }
+    };
+
+    println!(
+        "\n{} --- Running Custom Steps --- {}",
+        color::Fg(color::Yellow),
+        color::Fg(color::Reset)
+    );
+    let config_path = manifest::config_path();
+    if let Err(error) = run(&steps, Os::current(), config_path.parent()) {
+        eprintln!("{}", error);
+    }
+}
@@ -0,0 +1,285 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::os::unix::fs::symlink;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use termion::color;
+use toml::Value;
+
+use crate::config::manifest;
+use crate::system::path;
+use crate::system::shell::Shell;
+
+/// Dotfiles settings from the `[dotfiles]` table.
+///
+/// Links map a path relative to `$HOME` to a path relative to the checkout. A
+/// relative `path` is resolved against `$HOME`. Shell rc files that hold the
+/// instant-dev environment block (e.g. `~/.zshrc`) are never linked; source
+/// your dotfiles from them instead.
+///
+/// ```toml
+/// [dotfiles]
+/// repo = "git@github.com:acme/dotfiles.git"   # or "file:///srv/dotfiles"
+/// path = "~/.dotfiles"
+/// branch = "main"                               # optional
+/// ssh_key = "~/.ssh/id_ed25519"                 # optional
+///
+/// [dotfiles.links]
+/// ".vimrc" = "vim/vimrc"
+/// ".config/nvim" = "nvim"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dotfiles {
+    pub repo: String,
+    pub path: PathBuf,
+    pub branch: Option<String>,
+    pub ssh_key: String,
+    pub links: BTreeMap<String, String>,
+}
+
+/// What happened to a link target in `$HOME`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkStatus {
+    /// The target already pointed at the checkout.
+    Unchanged,
+    /// The target did not exist and was linked.
+    Created,
+    /// The target existed; it was moved to the backup path and then linked.
+    Replaced(PathBuf),
+}
+
+impl Dotfiles {
+    /// Read the `[dotfiles]` table, or `None` if the dotfiles stage is not configured.
+    pub fn from_config(config: &Value) -> Result<Option<Dotfiles>, String> {
+        let Some(dotfiles) = config.get("dotfiles") else {
+            return Ok(None);
+        };
+
+        let repo = dotfiles
+            .get("repo")
+            .and_then(Value::as_str)
+            .ok_or("[dotfiles] is missing `repo`")?;
+
+        let mut links = BTreeMap::new();
+        if let Some(table) = manifest::table(dotfiles, &["links"]) {
+            for (target, source) in table {
+                let source = source
+                    .as_str()
+                    .ok_or_else(|| format!("Dotfiles link '{}' must be a string", target))?;
+                links.insert(target.clone(), source.to_string());
+            }
+        }
+
+        let mut checkout = path::expand_home(
+            dotfiles
+                .get("path")
+                .and_then(Value::as_str)
+                .unwrap_or("~/.dotfiles"),
+        );
+        if checkout.is_relative() {
+            let home = dirs::home_dir().ok_or("Home directory not found")?;
+            checkout = home.join(checkout);
+        }
+
+        Ok(Some(Dotfiles {
+            repo: repo.to_string(),
+            path: checkout,
+            branch: dotfiles
+                .get("branch")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            ssh_key: dotfiles
+                .get("ssh_key")
+                .and_then(Value::as_str)
+                .unwrap_or("~/.ssh/id_ed25519")
+                .to_string(),
+            links,
+        }))
+    }
+
+    /// Build a git command that uses the configured SSH key for remote repos.
+    fn git(&self) -> Command {
+        let mut command = Command::new("git");
+        let key = path::expand_home(&self.ssh_key);
+        if !self.repo.starts_with("file://") && key.exists() {
+            command.env(
+                "GIT_SSH_COMMAND",
+                format!("ssh -i {} -o IdentitiesOnly=yes", key.display()),
+            );
+        }
+        command
+    }
+
+    /// Clone the repo, or fast-forward the existing checkout.
+    ///
+    /// Returns whether the repo was cloned.
+    pub fn clone_or_pull(&self) -> io::Result<bool> {
+        let mut command = self.git();
+        let cloned = !self.path.join(".git").exists();
+        if cloned {
+            command.arg("clone");
+            if let Some(branch) = &self.branch {
+                command.args(["--branch", branch]);
+            }
+            command.arg(&self.repo).arg(&self.path);
+        } else {
+            command
+                .arg("-C")
+                .arg(&self.path)
+                .args(["pull", "--ff-only"]);
+        }
+
+        let output = command.output()?;
+        if output.status.success() {
+            Ok(cloned)
+        } else {
+            Err(io::Error::other(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ))
+        }
+    }
+
+    /// Symlink every mapped file into `home`, backing up anything replaced.
+    ///
+    /// Links whose source is missing from the checkout are reported and skipped,
+    /// as are links that would replace a shell rc file instant-dev manages.
+    pub fn link(&self, home: &Path) -> Vec<(PathBuf, io::Result<LinkStatus>)> {
+        let rc_files: Vec<PathBuf> = [Shell::Zsh, Shell::Bash, Shell::Fish, Shell::Nushell]
+            .iter()
+            .map(|shell| shell.rc_file(home))
+            .collect();
+
+        self.links
+            .iter()
+            .map(|(target, source)| {
+                let target = home.join(target);
+                let status = match rc_files.iter().find(|rc_file| rc_file.starts_with(&target)) {
+                    Some(rc_file) => Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!(
+                            "{} holds the instant-dev shell environment; source {} from it instead",
+                            rc_file.display(),
+                            self.path.join(source).display()
+                        ),
+                    )),
+                    None => link_file(&self.path.join(source), &target),
+                };
+                (target, status)
+            })
+            .collect()
+    }
+}
+
+/// The first free backup path for `target`: `target.bak`, `target.bak.1`, ...
+pub fn backup_path(target: &Path) -> PathBuf {
+    let mut name = target.as_os_str().to_os_string();
+    name.push(".bak");
+    let mut backup = PathBuf::from(&name);
+    let mut index = 1;
+    while backup.symlink_metadata().is_ok() {
+        backup = PathBuf::from(format!("{}.{}", name.to_string_lossy(), index));
+        index += 1;
+    }
+    backup
+}
+
+/// Point `target` at `source`, moving any existing file out of the way.
+fn link_file(source: &Path, target: &Path) -> io::Result<LinkStatus> {
+    if !source.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} is not in the dotfiles repo", source.display()),
+        ));
+    }
+
+    let status = match target.symlink_metadata() {
+        Err(_) => LinkStatus::Created,
+        Ok(_) if fs::read_link(target).is_ok_and(|current| current == source) => {
+            return Ok(LinkStatus::Unchanged);
+        }
+        Ok(_) => {
+            let backup = backup_path(target);
+            fs::rename(target, &backup)?;
+            LinkStatus::Replaced(backup)
+        }
+    };
+
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    symlink(source, target)?;
+    Ok(status)
+}
+
+/// Load the `[dotfiles]` settings, reporting invalid configuration.
+fn load() -> Option<Dotfiles> {
+    match Dotfiles::from_config(&manifest::load()?) {
+        Ok(dotfiles) => dotfiles,
+        Err(error) => {
+            eprintln!("Invalid [dotfiles] configuration: {}", error);
+            None
+        }
+    }
+}
+
+/// Clone or update the dotfiles and refresh the links, printing each change.
+fn sync(dotfiles: &Dotfiles) {
+    match dotfiles.clone_or_pull() {
+        Ok(true) => println!("Cloned {} into {}.", dotfiles.repo, dotfiles.path.display()),
+        Ok(false) => println!("Updated {}.", dotfiles.path.display()),
+        Err(e) => {
+            eprintln!("Error syncing {}: {}", dotfiles.repo, e);
+            return;
+        }
+    }
+
+    let Some(home) = dirs::home_dir() else {
+        eprintln!("Home directory not found");
+        return;
+    };
+
+    for (target, status) in dotfiles.link(&home) {
+        match status {
+            Ok(LinkStatus::Unchanged) => {}
+            Ok(LinkStatus::Created) => println!("Linked {}.", target.display()),
+            Ok(LinkStatus::Replaced(backup)) => println!(
+                "Linked {} (previous file moved to {}).",
+                target.display(),
+                backup.display()
+            ),
+            Err(e) => eprintln!("Error linking {}: {}", target.display(), e),
+        }
+    }
+}
+
+/// Configure the dotfiles.
+///
+/// This function runs when `packages.toml` has a `[dotfiles]` section, after the Git
+/// stage has set up SSH access. It clones the repo (or fast-forwards an existing
+/// checkout) and symlinks the files listed under `[dotfiles.links]` into `$HOME`,
+/// moving anything it would replace to a `.bak` file. Shell rc files that hold the
+/// managed environment block are left alone.
+pub fn configure_dotfiles() {
+    let Some(dotfiles) = load() else {
+        return;
+    };
+
+    println!(
+        "\n{} --- Syncing dotfiles --- {}",
+        color::Fg(color::Yellow),
+        color::Fg(color::Reset)
+    );
+    sync(&dotfiles);
+}
+
+/// Pull the latest dotfiles and refresh the links, for `instant-dev dotfiles pull`.
+pub fn pull() {
+    match load() {
+        Some(dotfiles) => sync(&dotfiles),
+        None => eprintln!(
+            "No [dotfiles] section in {}.",
+            manifest::config_path().display()
+        ),
+    }
+}
@@ -2,17 +2,14 @@ use open;
 use std::fs;
 use std::fs::OpenOptions;
 use std::io::{self, Write};
-use std::process::{exit, Command, Stdio};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 use termion::color;
+use toml::Value;
 
-/// Run a command and return whether it was successful.
-fn run_command_success(command: &str, args: &[&str]) -> bool {
-    if let Ok(_) = Command::new(command).args(args).output() {
-        true
-    } else {
-        false
-    }
-}
+use crate::config::{gitconfig, gitignore, manifest, signing};
+use crate::system::clipboard::{self, Clipboard};
+use crate::system::path;
 
 /// Check if SSH authentication to GitHub is successful.
 fn check_git_authentication() -> bool {
@@ -35,18 +32,6 @@ fn check_git_authentication() -> bool {
     }
 }
 
-/// Configure Git details.
-fn configure_git_details(config: &str, value: &str) -> io::Result<()> {
-    if run_command_success("git", &["config", "--global", config, value]) {
-        Ok(())
-    } else {
-        Err(io::Error::new(
-            io::ErrorKind::Other,
-            format!("Error setting git {}", config),
-        ))
-    }
-}
-
 /// Open GitHub website for user to paste in SSH key and wait for return.
 fn open_github() {
     let path = "https://github.com/settings/keys";
@@ -159,26 +144,40 @@ fn generate_ssh_key(email: &str, verbose: bool) {
 }
 
 /// Copy the public key to the clipboard.
-fn copy_public_key(verbose: bool) {
-    let pbcopy_command = Command::new("sh")
-        .arg("-c")
-        .arg("pbcopy < ~/.ssh/id_ed25519.pub")
-        .output();
-
-    match pbcopy_command {
-        Ok(output) => {
-            if output.status.success() {
-                if verbose {
-                    println!("Public key copied to clipboard successfully.");
-                }
-            } else {
-                eprintln!("Error copying public key to clipboard: {:?}", output.stderr);
-                exit(1);
+///
+/// Falls back to printing the key when no clipboard is available, so a
+/// missing clipboard tool never aborts the setup.
+fn copy_public_key(public_key_path: &Path, verbose: bool) {
+    let public_key = match fs::read_to_string(public_key_path) {
+        Ok(key) => key.trim().to_string(),
+        Err(error) => {
+            eprintln!("Error reading public key: {}", error);
+            return;
+        }
+    };
+
+    let print_key = || {
+        println!(
+            "{}{}{}",
+            color::Fg(color::Green),
+            public_key,
+            color::Fg(color::Reset)
+        )
+    };
+    match clipboard::copy(&public_key) {
+        // OSC 52 cannot tell whether the terminal honoured it, so show the key too.
+        Some(Clipboard::Osc52) => {
+            println!("Asked the terminal to copy the public key. If it did not, copy it below:");
+            print_key();
+        }
+        Some(backend) => {
+            if verbose {
+                println!("Public key copied to clipboard with {}.", backend.name());
             }
         }
-        Err(error) => {
-            eprintln!("Error running pbcopy command: {}", error);
-            exit(1);
+        None => {
+            println!("No clipboard available. Copy the public key below:");
+            print_key();
         }
     }
 }
@@ -197,17 +196,194 @@ fn configure_ssh(email: &str, verbose: bool) {
             println!("SSH configuration generated successfully.");
         }
     }
-    copy_public_key(verbose);
+    copy_public_key(&path::expand_home("~/.ssh/id_ed25519.pub"), verbose);
     open_github();
 }
 
 /// Set Git details like username and email.
 fn set_details(user: &str, email: &str) -> io::Result<()> {
-    configure_git_details("user.name", user)?;
-    configure_git_details("user.email", email)?;
+    gitconfig::set_global("user.name", user)?;
+    gitconfig::set_global("user.email", email)?;
     Ok(())
 }
 
+/// A git identity used for repositories under a directory.
+///
+/// Profiles are declared in `packages.toml` under `[git.profiles.<name>]`:
+///
+/// ```toml
+/// [git.profiles.work]
+/// directory = "~/work/**"
+/// user = "Jane Doe"
+/// email = "jane@acme.com"
+/// ssh_key = "~/.ssh/id_ed25519_work" # optional
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitProfile {
+    pub name: String,
+    pub directory: String,
+    pub user: String,
+    pub email: String,
+    pub ssh_key: String,
+}
+
+impl GitProfile {
+    /// Build a profile from its `[git.profiles.<name>]` table.
+    pub fn from_value(name: &str, value: &Value) -> Result<GitProfile, String> {
+        let field = |key: &str| {
+            value
+                .get(key)
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .ok_or_else(|| format!("git profile '{}' is missing '{}'", name, key))
+        };
+
+        Ok(GitProfile {
+            name: name.to_string(),
+            directory: field("directory")?,
+            user: field("user")?,
+            email: field("email")?,
+            ssh_key: field("ssh_key").unwrap_or_else(|_| format!("~/.ssh/id_ed25519_{}", name)),
+        })
+    }
+
+    /// The `gitdir:` pattern matching repositories in the profile directory.
+    pub fn gitdir(&self) -> String {
+        if self.directory.ends_with('/') || self.directory.ends_with("/**") {
+            self.directory.clone()
+        } else {
+            format!("{}/", self.directory)
+        }
+    }
+
+    /// Where the profile's gitconfig include is written.
+    pub fn include_path(&self) -> PathBuf {
+        path::expand_home("~/.config/git/profiles").join(format!("{}.gitconfig", self.name))
+    }
+
+    /// Contents of the gitconfig include holding the profile identity and SSH key.
+    pub fn include_contents(&self) -> String {
+        let ssh_command = format!("ssh -i {} -o IdentitiesOnly=yes", self.ssh_key);
+        format!(
+            "# Generated by instant-dev for the '{}' profile.\n\
+             [user]\n\tname = {}\n\temail = {}\n\
+             [core]\n\tsshCommand = {}\n",
+            self.name,
+            quote_git_value(&self.user),
+            quote_git_value(&self.email),
+            quote_git_value(&ssh_command)
+        )
+    }
+}
+
+/// Quote a value for a gitconfig file.
+fn quote_git_value(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Read the `[git.profiles]` tables from the config.
+///
+/// Invalid profiles are reported and skipped.
+pub fn git_profiles(config: &Value) -> Vec<GitProfile> {
+    let Some(profiles) = manifest::table(config, &["git", "profiles"]) else {
+        return Vec::new();
+    };
+
+    profiles
+        .iter()
+        .filter_map(|(name, value)| match GitProfile::from_value(name, value) {
+            Ok(profile) => Some(profile),
+            Err(error) => {
+                eprintln!("{}", error);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Path of the public key for the private key at `key_path`.
+///
+/// `.pub` is appended rather than swapped in, so keys like `id_ed25519_acme.corp`
+/// keep their full name.
+pub fn public_key_path(key_path: &Path) -> PathBuf {
+    let mut public = key_path.as_os_str().to_owned();
+    public.push(".pub");
+    PathBuf::from(public)
+}
+
+/// Generate a dedicated SSH key for a profile at `key_path`.
+fn generate_profile_key(key_path: &Path, email: &str) -> io::Result<()> {
+    if let Some(parent) = key_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let output = Command::new("ssh-keygen")
+        .args(["-t", "ed25519", "-C", email, "-N", "", "-f"])
+        .arg(key_path)
+        .stdin(Stdio::null())
+        .output()?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "ssh-keygen failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+/// Write the include file for a profile and register it with `includeIf`.
+fn apply_profile(profile: &GitProfile, verbose: bool) -> io::Result<()> {
+    let key_path = path::expand_home(&profile.ssh_key);
+    if !key_path.exists() {
+        generate_profile_key(&key_path, &profile.email)?;
+        println!(
+            "Generated SSH key {} for the '{}' profile. Add it to that account.",
+            profile.ssh_key, profile.name
+        );
+        copy_public_key(&public_key_path(&key_path), verbose);
+        open_github();
+    }
+
+    let include_path = profile.include_path();
+    if let Some(parent) = include_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&include_path, profile.include_contents())?;
+
+    gitconfig::set_global(
+        &format!("includeIf.gitdir:{}.path", profile.gitdir()),
+        &include_path.to_string_lossy(),
+    )?;
+
+    if verbose {
+        println!(
+            "Profile '{}' applied to {}.",
+            profile.name,
+            profile.gitdir()
+        );
+    }
+    Ok(())
+}
+
+/// Apply the per-directory git identities declared in the config.
+fn configure_profiles(verbose: bool) {
+    let Some(config) = manifest::load() else {
+        return;
+    };
+
+    for profile in git_profiles(&config) {
+        match apply_profile(&profile, verbose) {
+            Ok(()) => println!("Git profile '{}' configured.", profile.name),
+            Err(error) => eprintln!(
+                "Error configuring git profile '{}': {}",
+                profile.name, error
+            ),
+        }
+    }
+}
+
 /// Configure Git, including SSH setup and user details.
 ///
 /// This function configures Git for GitHub access, including setting up SSH keys,
@@ -216,7 +392,9 @@ fn set_details(user: &str, email: &str) -> io::Result<()> {
 /// # Arguments
 ///
 /// * `verbose` - A boolean indicating whether to print verbose output.
-pub fn configure_git(verbose: bool) {
+///
+/// Returns whether GitHub access works once the stage is done.
+pub fn configure_git(verbose: bool) -> bool {
     let user: &str = "jtfletch";
     let email: &str = "jobetfletcher@gmail.com";
 
@@ -226,8 +404,9 @@ pub fn configure_git(verbose: bool) {
         color::Fg(color::Reset)
     );
 
-    if check_git_authentication() {
+    let authenticated = if check_git_authentication() {
         println!("GitHub is already configured.");
+        true
     } else {
         println!(
             "{}Configuring GitHub{}",
@@ -242,10 +421,18 @@ pub fn configure_git(verbose: bool) {
         }
         configure_ssh(email, verbose);
 
-        if check_git_authentication() {
+        let authenticated = check_git_authentication();
+        if authenticated {
             println!("GitHub access configured successfully.");
         } else {
             eprintln!("Error configuring GitHub access.");
         }
-    }
+        authenticated
+    };
+
+    gitconfig::configure_settings(verbose);
+    gitignore::configure_gitignore(verbose);
+    configure_profiles(verbose);
+    signing::configure_signing(verbose);
+    authenticated
 }
@@ -0,0 +1,149 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::process::Command;
+use toml::Value;
+
+use crate::config::manifest;
+use crate::system::settings::{diff_settings, print_diff};
+
+/// Read a value from the global git config.
+///
+/// Returns `None` if the key is unset or git is unavailable.
+pub fn get_global(key: &str) -> Option<String> {
+    let output = Command::new("git")
+        .args(["config", "--global", "--get", key])
+        .output()
+        .ok()?;
+
+    if output.status.success() {
+        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        None
+    }
+}
+
+/// Write a value to the global git config.
+pub fn set_global(key: &str, value: &str) -> io::Result<()> {
+    let output = Command::new("git")
+        .args(["config", "--global", key, value])
+        .output()?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "Error setting git {}: {}",
+            key,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+/// Settings applied by `preset = "team"` under `[git]`.
+pub fn team_preset() -> Vec<(&'static str, &'static str)> {
+    vec![
+        ("init.defaultBranch", "main"),
+        ("pull.rebase", "true"),
+        ("rebase.autoStash", "true"),
+        ("rerere.enabled", "true"),
+        ("fetch.prune", "true"),
+        ("push.autoSetupRemote", "true"),
+        ("diff.colorMoved", "zebra"),
+    ]
+}
+
+/// Render a TOML value as a git config value.
+fn git_value(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Boolean(b) => Some(b.to_string()),
+        Value::Integer(i) => Some(i.to_string()),
+        Value::Float(f) => Some(f.to_string()),
+        _ => None,
+    }
+}
+
+/// Flatten nested tables into dotted git keys, e.g. `[pull] rebase` to `pull.rebase`.
+fn flatten(prefix: &str, table: &toml::value::Table, settings: &mut BTreeMap<String, String>) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+
+        match value {
+            Value::Table(nested) => flatten(&key, nested, settings),
+            other => match git_value(other) {
+                Some(value) => {
+                    settings.insert(key, value);
+                }
+                None => eprintln!("Unsupported value for git setting '{}'", key),
+            },
+        }
+    }
+}
+
+/// Collect the desired global git settings from the config.
+///
+/// The team preset is applied first, then `[git.config]` overrides it, and
+/// `[git.aliases]` entries become `alias.<name>` settings.
+pub fn desired_settings(config: &Value) -> BTreeMap<String, String> {
+    let mut settings = BTreeMap::new();
+
+    match config
+        .get("git")
+        .and_then(|git| git.get("preset"))
+        .and_then(Value::as_str)
+    {
+        Some("team") => {
+            for (key, value) in team_preset() {
+                settings.insert(key.to_string(), value.to_string());
+            }
+        }
+        Some(other) => eprintln!("Unknown git preset '{}'", other),
+        None => {}
+    }
+
+    if let Some(table) = manifest::table(config, &["git", "config"]) {
+        flatten("", table, &mut settings);
+    }
+    if let Some(table) = manifest::table(config, &["git", "aliases"]) {
+        flatten("alias", table, &mut settings);
+    }
+    settings
+}
+
+/// Apply the `[git.config]`, `[git.aliases]` and preset settings idempotently.
+///
+/// Only settings whose current value differs are written.
+///
+/// # Arguments
+///
+/// * `verbose` - A boolean indicating whether to print verbose output.
+pub fn configure_settings(verbose: bool) {
+    let Some(config) = manifest::load() else {
+        return;
+    };
+
+    let desired = desired_settings(&config);
+    if desired.is_empty() {
+        return;
+    }
+
+    let changes = diff_settings(&desired, get_global);
+    if changes.is_empty() {
+        if verbose {
+            println!("Global git settings are up to date.");
+        }
+        return;
+    }
+
+    println!("Updating global git settings:");
+    print_diff(&changes);
+    for change in &changes {
+        if let Err(error) = set_global(&change.key, &change.desired) {
+            eprintln!("{}", error);
+        }
+    }
+}
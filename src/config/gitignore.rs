@@ -0,0 +1,136 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use termion::color;
+use toml::Value;
+
+use crate::config::{gitconfig, manifest};
+use crate::system::{managed, path};
+
+/// Ignore patterns for a built-in template name.
+pub fn template(name: &str) -> Option<&'static [&'static str]> {
+    let patterns: &'static [&'static str] = match name {
+        "macos" => &[".DS_Store", ".AppleDouble", ".LSOverride", "._*"],
+        "jetbrains" => &[".idea/", "*.iml"],
+        "vscode" => &[".vscode/"],
+        "vim" => &["*.swp", "*.swo", "Session.vim"],
+        "env" => &[".env", ".env.local", ".envrc"],
+        "rust" => &["target/"],
+        "go" => &["vendor/"],
+        "node" => &["node_modules/", "npm-debug.log*"],
+        "python" => &["__pycache__/", "*.py[cod]", ".venv/"],
+        _ => return None,
+    };
+    Some(patterns)
+}
+
+/// Collect the ignore patterns declared in `[gitignore]`.
+///
+/// ```toml
+/// [gitignore]
+/// templates = ["macos", "jetbrains", "rust", "env"]
+/// patterns = ["*.local"]
+/// ```
+pub fn configured_patterns(config: &Value) -> Vec<String> {
+    let mut patterns: Vec<String> = Vec::new();
+
+    for name in manifest::strings(config, &["gitignore", "templates"]) {
+        match template(&name) {
+            Some(lines) => patterns.extend(lines.iter().map(|line| line.to_string())),
+            None => eprintln!("Unknown gitignore template '{}'", name),
+        }
+    }
+    patterns.extend(manifest::strings(config, &["gitignore", "patterns"]));
+
+    let mut unique = Vec::new();
+    for pattern in patterns {
+        if !unique.contains(&pattern) {
+            unique.push(pattern);
+        }
+    }
+    unique
+}
+
+/// Merge `patterns` into an existing ignore file.
+///
+/// Lines the user wrote are kept as-is, and patterns they already have are
+/// not repeated in the managed block.
+pub fn merge_ignore(existing: &str, patterns: &[String]) -> String {
+    let user_content = managed::strip_block(existing);
+    let user_lines: Vec<&str> = user_content.lines().map(str::trim).collect();
+
+    let managed_lines: Vec<String> = patterns
+        .iter()
+        .filter(|pattern| !user_lines.contains(&pattern.as_str()))
+        .cloned()
+        .collect();
+
+    managed::replace_block(&user_content, &managed_lines)
+}
+
+/// Path of the global ignore file maintained by instant-dev.
+pub fn ignore_path() -> PathBuf {
+    match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir).join("git").join("ignore"),
+        _ => path::expand_home("~/.config/git/ignore"),
+    }
+}
+
+/// Write the merged ignore file and point `core.excludesfile` at it if another
+/// file is configured.
+fn apply_ignore(patterns: &[String], verbose: bool) -> io::Result<()> {
+    let target = ignore_path();
+    let excludes = gitconfig::get_global("core.excludesfile").map(|file| path::expand_home(&file));
+
+    // Carry over patterns from a previously configured excludes file.
+    let mut existing = fs::read_to_string(&target).unwrap_or_default();
+    if let Some(other) = excludes.as_deref().filter(|file| *file != target) {
+        let previous = fs::read_to_string(other).unwrap_or_default();
+        existing = format!("{}{}", previous, existing);
+    }
+
+    let merged = merge_ignore(&existing, patterns);
+    if merged != fs::read_to_string(&target).unwrap_or_default() {
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&target, merged)?;
+        println!("Updated {}.", target.display());
+    } else if verbose {
+        println!("{} is up to date.", target.display());
+    }
+
+    // Git reads the default ignore file on its own unless another one is set.
+    if excludes.is_some_and(|file| file != target) {
+        gitconfig::set_global("core.excludesfile", &target.to_string_lossy())?;
+        println!("Set core.excludesfile to {}.", target.display());
+    }
+    Ok(())
+}
+
+/// Maintain the global gitignore declared in `[gitignore]`.
+///
+/// # Arguments
+///
+/// * `verbose` - A boolean indicating whether to print verbose output.
+pub fn configure_gitignore(verbose: bool) {
+    let Some(config) = manifest::load() else {
+        return;
+    };
+
+    let patterns = configured_patterns(&config);
+    if patterns.is_empty() {
+        return;
+    }
+
+    println!(
+        "\n{} --- Configuring global gitignore --- {}",
+        color::Fg(color::Yellow),
+        color::Fg(color::Reset)
+    );
+
+    if let Err(error) = apply_ignore(&patterns, verbose) {
+        eprintln!("Error configuring global gitignore: {}", error);
+    }
+}
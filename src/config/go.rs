@@ -1,101 +1,408 @@
+use std::collections::BTreeMap;
 use std::env;
-use std::fs::{self, File};
-use std::io::{BufRead, BufReader, Write};
-use std::path::Path;
-use termion::color;
-
-/// Print a message to instruct the user to source .zshrc.
-fn source_zshrc() {
-    println!("\n-----------------------------------------------");
-    println!("Please run the following command in your shell:");
-    println!(
-        "{}source ~/.zshrc{}",
-        color::Fg(color::Green),
-        color::Fg(color::Reset)
-    );
-    println!("-----------------------------------------------");
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use toml::Value;
+
+use crate::config::toolchain::{Tool, Toolchain};
+use crate::config::{gitconfig, manifest};
+use crate::system::{exec, settings};
+
+/// Read a Go environment variable with `go env`.
+fn go_env(key: &str) -> Option<String> {
+    let output = Command::new("go").args(["env", key]).output().ok()?;
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if output.status.success() && !value.is_empty() {
+        Some(value)
+    } else {
+        None
+    }
 }
 
 /// Check if Go is installed.
-fn check_go_installed() -> bool {
-    match std::process::Command::new("brew").arg("list").output() {
-        Ok(output) => {
-            if let Ok(output_str) = String::from_utf8(output.stdout) {
-                output_str.contains("go")
-            } else {
-                false
-            }
-        }
-        Err(_) => false,
-    }
+pub fn check_go_installed() -> bool {
+    Command::new("go")
+        .arg("version")
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
 }
 
-/// Configure the Go environment.
+/// A Go tool installed with `go install`.
 ///
-/// This function checks if Go is installed using Homebrew. If Go is installed, it installs
-/// necessary Go tools such as `goimports` and `swag`. It also ensures that the necessary
-/// environment variables are set in the user's shell configuration file (`~/.zshrc`).
-/// If the configuration file does not exist, it creates it and appends the required
-/// environment variable settings. If the required settings already exist, it skips this step.
-/// Finally, it prompts the user to source the shell configuration file to apply the changes.
+/// Tools are declared in `packages.toml` as `package@version` strings or
+/// tables, and default to `latest`:
 ///
-/// Note: This function assumes the user is using the Zsh shell. If you're using a different
-/// shell, you may need to manually add the necessary environment variable settings.
-pub fn configure_go() {
-    // Check if Go is installed using Homebrew
-    let go_installed = check_go_installed();
-
-    if go_installed {
-        // Install Go Tools
-        if let Err(e) = std::process::Command::new("go")
-            .args(&["install", "golang.org/x/tools/cmd/goimports@latest"])
-            .status()
-        {
-            eprintln!("Error installing goimports: {}", e);
-            return;
+/// ```toml
+/// [go]
+/// tools = [
+///   "golang.org/x/tools/cmd/goimports",
+///   "github.com/swaggo/swag/cmd/swag@v1.16.3",
+///   { package = "github.com/go-delve/delve/cmd/dlv", version = "v1.22.1" },
+/// ]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoTool {
+    pub package: String,
+    pub version: String,
+}
+
+impl GoTool {
+    /// Parse a tool entry from the `[go] tools` list.
+    pub fn from_value(value: &Value) -> Option<GoTool> {
+        let (package, version) = match value {
+            Value::String(spec) => match spec.split_once('@') {
+                Some((package, version)) => (package, Some(version)),
+                None => (spec.as_str(), None),
+            },
+            Value::Table(table) => (
+                table.get("package").and_then(Value::as_str)?,
+                table.get("version").and_then(Value::as_str),
+            ),
+            _ => return None,
+        };
+
+        if package.is_empty() {
+            return None;
         }
+        Some(GoTool {
+            package: package.to_string(),
+            version: version.unwrap_or("latest").to_string(),
+        })
+    }
+
+    /// Name of the binary `go install` produces, skipping a `/vN` major version suffix.
+    pub fn binary(&self) -> String {
+        let mut segments = self.package.rsplit('/');
+        let last = segments.next().unwrap_or_default();
+        let is_major_suffix = last.len() > 1
+            && last.starts_with('v')
+            && last[1..].chars().all(|c| c.is_ascii_digit());
 
-        if let Err(e) = std::process::Command::new("go")
-            .args(&["install", "github.com/swaggo/swag/cmd/swag@latest"])
-            .status()
-        {
-            eprintln!("Error installing swagger: {}", e);
-            return;
+        if is_major_suffix {
+            segments.next().unwrap_or(last).to_string()
+        } else {
+            last.to_string()
         }
+    }
 
-        // Check if ~/.zshrc exists
-        let zshrc_path = Path::new(&env::var("HOME").unwrap()).join(".zshrc");
-        if zshrc_path.exists() {
-            // Read ~/.zshrc and check if the line exists
-            let mut contains_line = false;
-            if let Ok(file) = File::open(&zshrc_path) {
-                let reader = BufReader::new(file);
-                for line in reader.lines() {
-                    if let Ok(line) = line {
-                        if line.contains("export PATH=${PATH}:`go env GOPATH`/bin") {
-                            contains_line = true;
-                            break;
-                        }
-                    }
-                }
+    /// The `package@version` argument passed to `go install`.
+    pub fn install_target(&self) -> String {
+        format!("{}@{}", self.package, self.version)
+    }
+}
+
+/// Tools installed when the config does not declare `[go] tools`.
+fn default_tools() -> Vec<GoTool> {
+    [
+        "golang.org/x/tools/cmd/goimports",
+        "github.com/swaggo/swag/cmd/swag",
+    ]
+    .into_iter()
+    .filter_map(|package| GoTool::from_value(&Value::String(package.to_string())))
+    .collect()
+}
+
+/// Read the Go tools to install from the config.
+pub fn go_tools(config: Option<&Value>) -> Vec<GoTool> {
+    let Some(tools) = config
+        .and_then(|config| config.get("go"))
+        .and_then(|go| go.get("tools"))
+        .and_then(Value::as_array)
+    else {
+        return default_tools();
+    };
+
+    tools
+        .iter()
+        .filter_map(|value| {
+            let tool = GoTool::from_value(value);
+            if tool.is_none() {
+                eprintln!("Invalid Go tool entry: {}", value);
             }
+            tool
+        })
+        .collect()
+}
+
+/// Read the module version from `go version -m` output.
+pub fn module_version(output: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            ["mod", _, version, ..] => Some(version.to_string()),
+            _ => None,
+        }
+    })
+}
+
+/// Read a tool from `go version -m` output: its `path` line and module version.
+///
+/// Binaries built from a local checkout report `(devel)` and are recorded as `latest`.
+pub fn tool_from_build_info(output: &str) -> Option<GoTool> {
+    let package = output.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            ["path", package, ..] => Some(package.to_string()),
+            _ => None,
+        }
+    })?;
+    let version = module_version(output)
+        .filter(|version| version != "(devel)")
+        .unwrap_or_else(|| "latest".to_string());
+    Some(GoTool { package, version })
+}
+
+/// List the tools `go install` has put in `GOBIN` or `GOPATH/bin`.
+pub fn installed_tools() -> Vec<GoTool> {
+    let Some(entries) = gobin().and_then(|bin_dir| fs::read_dir(bin_dir).ok()) else {
+        return Vec::new();
+    };
+
+    let mut tools: Vec<GoTool> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let output = Command::new("go")
+                .args(["version", "-m"])
+                .arg(entry.path())
+                .output()
+                .ok()?;
+            tool_from_build_info(&String::from_utf8_lossy(&output.stdout))
+        })
+        .collect();
+    tools.sort_by(|a, b| a.package.cmp(&b.package));
+    tools
+}
+
+/// Directory `go install` writes binaries to: `GOBIN`, or `GOPATH/bin`.
+fn gobin() -> Option<PathBuf> {
+    go_env("GOBIN")
+        .map(PathBuf::from)
+        .or_else(|| go_env("GOPATH").and_then(|gopath| gopath_bin(&gopath)))
+}
+
+/// The `bin` directory of the first entry in `gopath`, which is where
+/// `go install` puts binaries when `GOPATH` lists several directories.
+pub fn gopath_bin(gopath: &str) -> Option<PathBuf> {
+    env::split_paths(gopath)
+        .find(|dir| !dir.as_os_str().is_empty())
+        .map(|dir| dir.join("bin"))
+}
+
+/// A [`GoTool`] checked against the binaries in `bin_dir`.
+struct GoToolInstall {
+    tool: GoTool,
+    bin_dir: PathBuf,
+}
+
+impl Tool for GoToolInstall {
+    fn name(&self) -> String {
+        self.tool.binary()
+    }
+
+    /// Tools pinned to `latest` only need to exist in `bin_dir`.
+    fn is_installed(&self) -> bool {
+        let binary = self.bin_dir.join(self.tool.binary());
+        if !binary.exists() {
+            return false;
+        }
+        if self.tool.version == "latest" {
+            return true;
+        }
+
+        Command::new("go")
+            .args(["version", "-m"])
+            .arg(&binary)
+            .output()
+            .ok()
+            .and_then(|output| module_version(&String::from_utf8_lossy(&output.stdout)))
+            .is_some_and(|version| version == self.tool.version)
+    }
+
+    fn install(&self) -> io::Result<()> {
+        exec::run(Command::new("go").args(["install", &self.tool.install_target()]))
+    }
+}
 
-            // If the line doesn't exist, append it
-            if !contains_line {
-                if let Ok(mut file) = fs::OpenOptions::new().append(true).open(&zshrc_path) {
-                    if let Err(e) = writeln!(file, "export PATH=${{PATH}}:`go env GOPATH`/bin") {
-                        eprintln!("Error writing to .zshrc: {}", e);
-                    }
+/// Read the desired Go environment from the `[go.env]` table.
+///
+/// Array values are joined with commas, as `GOPRIVATE` and `GOPROXY` expect.
+pub fn desired_go_env(config: &Value) -> BTreeMap<String, String> {
+    let Some(table) = manifest::table(config, &["go", "env"]) else {
+        return BTreeMap::new();
+    };
+
+    table
+        .iter()
+        .filter_map(|(key, value)| {
+            let value = match value {
+                Value::String(value) => value.clone(),
+                Value::Boolean(value) => value.to_string(),
+                Value::Integer(value) => value.to_string(),
+                Value::Array(items) => items
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<_>>()
+                    .join(","),
+                _ => {
+                    eprintln!("Unsupported value for Go setting '{}'", key);
+                    return None;
                 }
-            }
+            };
+            Some((key.clone(), value))
+        })
+        .collect()
+}
+
+/// Derive git `url.<base>.insteadOf` rewrites that fetch private modules over SSH.
+///
+/// Each `GOPRIVATE` pattern such as `github.com/acme/*` maps
+/// `https://github.com/acme/` to `git@github.com:acme/`. Patterns with a
+/// wildcard host cannot be rewritten and are skipped.
+pub fn insteadof_rewrites(goprivate: &str) -> BTreeMap<String, String> {
+    let mut rewrites = BTreeMap::new();
+
+    for pattern in goprivate.split(',').map(str::trim) {
+        let pattern = pattern
+            .trim_end_matches("/...")
+            .trim_end_matches("/*")
+            .trim_end_matches('/');
+        let (host, path) = pattern.split_once('/').unwrap_or((pattern, ""));
+        if host.is_empty() || host.contains('*') || path.contains('*') {
+            continue;
+        }
+
+        let (ssh, https) = if path.is_empty() {
+            (format!("git@{}:", host), format!("https://{}/", host))
         } else {
-            // Create ~/.zshrc and write the line into it
-            if let Ok(mut file) = File::create(&zshrc_path) {
-                if let Err(e) = writeln!(file, "export PATH=${{PATH}}:`go env GOPATH`/bin") {
-                    eprintln!("Error writing to .zshrc: {}", e);
-                }
+            (
+                format!("git@{}:{}/", host, path),
+                format!("https://{}/{}/", host, path),
+            )
+        };
+        rewrites.insert(format!("url.{}.insteadOf", ssh), https);
+    }
+    rewrites
+}
+
+/// Apply `[go.env]` with `go env -w`, showing a diff of the changed values.
+fn configure_go_env(desired: &BTreeMap<String, String>) {
+    let changes = settings::diff_settings(desired, go_env);
+    if !changes.is_empty() {
+        println!("Updating Go environment:");
+        settings::print_diff(&changes);
+    }
+
+    for change in &changes {
+        let assignment = format!("{}={}", change.key, change.desired);
+        match Command::new("go").args(["env", "-w", &assignment]).output() {
+            Ok(output) if output.status.success() => {}
+            Ok(output) => eprintln!(
+                "Error setting {}: {}",
+                change.key,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            Err(e) => eprintln!("Error setting {}: {}", change.key, e),
+        }
+
+        // `go env -w` values are ignored while the variable is set in the shell.
+        if env::var(&change.key).is_ok_and(|value| value != change.desired) {
+            eprintln!(
+                "{} is set in your shell environment and overrides `go env -w`.",
+                change.key
+            );
+        }
+    }
+
+    if let Some(goprivate) = desired.get("GOPRIVATE") {
+        let rewrites = insteadof_rewrites(goprivate);
+        let changes = settings::diff_settings(&rewrites, gitconfig::get_global);
+        if !changes.is_empty() {
+            println!("Fetching private modules over SSH:");
+            settings::print_diff(&changes);
+        }
+        for change in &changes {
+            if let Err(e) = gitconfig::set_global(&change.key, &change.desired) {
+                eprintln!("{}", e);
             }
         }
-        source_zshrc();
+    }
+}
+
+/// The Go toolchain.
+///
+/// Go itself is installed through `packages.toml` (the `go` formula). This stage
+/// installs the tools listed under `[go] tools` (`goimports` and `swag` by default),
+/// skipping any whose binary is already in `GOPATH/bin` at the requested version.
+/// Settings from `[go.env]` are applied with `go env -w`, private modules listed in
+/// `GOPRIVATE` are fetched over SSH, and `GOPATH/bin` is added to the managed shell
+/// environment.
+pub struct GoToolchain {
+    tools: Vec<GoTool>,
+    env: BTreeMap<String, String>,
+}
+
+impl GoToolchain {
+    /// Read the Go settings from the config.
+    pub fn new(config: Option<&Value>) -> GoToolchain {
+        GoToolchain {
+            tools: go_tools(config),
+            env: config.map(desired_go_env).unwrap_or_default(),
+        }
+    }
+}
+
+impl Toolchain for GoToolchain {
+    fn name(&self) -> &'static str {
+        "Go"
+    }
+
+    fn section(&self) -> &'static str {
+        "go"
+    }
+
+    fn detect(&self) -> bool {
+        check_go_installed()
+    }
+
+    fn install(&self) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "Go is not installed; add `go` to packages in packages.toml",
+        ))
+    }
+
+    fn configure(&self) -> io::Result<()> {
+        if !self.env.is_empty() {
+            configure_go_env(&self.env);
+        }
+        Ok(())
+    }
+
+    fn tools(&self) -> Vec<Box<dyn Tool>> {
+        let Some(bin_dir) = gobin() else {
+            eprintln!("Error reading GOPATH from `go env`");
+            return Vec::new();
+        };
+
+        self.tools
+            .iter()
+            .map(|tool| {
+                Box::new(GoToolInstall {
+                    tool: tool.clone(),
+                    bin_dir: bin_dir.clone(),
+                }) as Box<dyn Tool>
+            })
+            .collect()
+    }
+
+    fn env_paths(&self) -> Vec<PathBuf> {
+        gobin().into_iter().collect()
+    }
+
+    fn verify(&self) -> io::Result<()> {
+        exec::run(Command::new("go").arg("version").stdout(Stdio::null()))
     }
 }
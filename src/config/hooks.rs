@@ -0,0 +1,219 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+use std::process::Stdio;
+use toml::Value;
+
+use crate::config::manifest;
+use crate::system::exec;
+
+/// What to do when a hook fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnFailure {
+    /// Stop the setup run.
+    Abort,
+    /// Report the failure and carry on.
+    Warn,
+}
+
+impl OnFailure {
+    fn from_value(value: Option<&Value>, default: OnFailure) -> Result<OnFailure, String> {
+        match value.map(|value| value.as_str()) {
+            None => Ok(default),
+            Some(Some("abort")) => Ok(OnFailure::Abort),
+            Some(Some("warn")) => Ok(OnFailure::Warn),
+            Some(_) => Err("`on_failure` must be \"abort\" or \"warn\"".to_string()),
+        }
+    }
+}
+
+/// Whether a hook runs before or after its stage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum When {
+    Pre,
+    Post,
+}
+
+impl When {
+    /// Name of the hook, as in the config and `INSTANT_DEV_HOOK`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            When::Pre => "pre",
+            When::Post => "post",
+        }
+    }
+}
+
+/// The hooks of one stage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StageHooks {
+    pub pre: Vec<String>,
+    pub post: Vec<String>,
+    pub on_failure: OnFailure,
+}
+
+impl StageHooks {
+    /// The commands to run at `when`.
+    pub fn commands(&self, when: When) -> &[String] {
+        match when {
+            When::Pre => &self.pre,
+            When::Post => &self.post,
+        }
+    }
+}
+
+/// The setup stages hooks can attach to, in the order they run.
+///
+/// Taps, casks and Mac App Store apps are installed in the `packages` stage.
+pub const STAGES: &[&str] = &[
+    "brew",
+    "packages",
+    "go",
+    "rust",
+    "node",
+    "python",
+    "custom",
+    "git",
+    "dotfiles",
+    "workspace",
+    "macos",
+];
+
+/// Shell commands run before and after setup stages, from the `[hooks]` table.
+///
+/// Stages are listed in [`STAGES`]; casks are part of `packages`. Unknown stages
+/// and settings are rejected, and setup stops before running any stage.
+///
+/// ```toml
+/// [hooks]
+/// on_failure = "warn"      # default for every stage; or "abort"
+///
+/// [hooks.packages]
+/// post = "./scripts/install-vpn-profile.sh"
+///
+/// [hooks.git]
+/// post = ["gh extension install dlvhdr/gh-dash"]
+/// on_failure = "abort"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Hooks {
+    pub stages: BTreeMap<String, StageHooks>,
+}
+
+/// Read a command or list of commands.
+fn commands(value: Option<&Value>) -> Result<Vec<String>, String> {
+    match value {
+        None => Ok(Vec::new()),
+        Some(Value::String(command)) => Ok(vec![command.clone()]),
+        Some(Value::Array(commands)) => commands
+            .iter()
+            .map(|command| {
+                command
+                    .as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| format!("hook commands must be strings, found {}", command))
+            })
+            .collect(),
+        Some(value) => Err(format!(
+            "hooks must be a command or a list of commands, found {}",
+            value
+        )),
+    }
+}
+
+impl Hooks {
+    /// Read the `[hooks]` table; a missing table means no hooks.
+    pub fn from_config(config: &Value) -> Result<Hooks, String> {
+        let Some(table) = manifest::table(config, &["hooks"]) else {
+            return Ok(Hooks::default());
+        };
+        let default = OnFailure::from_value(table.get("on_failure"), OnFailure::Warn)?;
+
+        let mut stages = BTreeMap::new();
+        for (stage, hooks) in table {
+            if stage == "on_failure" {
+                continue;
+            }
+            if !STAGES.contains(&stage.as_str()) {
+                return Err(format!(
+                    "Unknown stage '{}' in [hooks]; expected one of {}",
+                    stage,
+                    STAGES.join(", ")
+                ));
+            }
+            let Some(settings) = hooks.as_table() else {
+                return Err(format!("[hooks.{}] must be a table", stage));
+            };
+            if let Some(key) = settings
+                .keys()
+                .find(|key| !["pre", "post", "on_failure"].contains(&key.as_str()))
+            {
+                return Err(format!(
+                    "[hooks.{}]: unknown setting `{}`; expected pre, post or on_failure",
+                    stage, key
+                ));
+            }
+
+            let error = |e: String| format!("[hooks.{}]: {}", stage, e);
+            let on_failure =
+                OnFailure::from_value(hooks.get("on_failure"), default).map_err(error)?;
+            stages.insert(
+                stage.clone(),
+                StageHooks {
+                    pre: commands(hooks.get("pre")).map_err(error)?,
+                    post: commands(hooks.get("post")).map_err(error)?,
+                    on_failure,
+                },
+            );
+        }
+        Ok(Hooks { stages })
+    }
+
+    /// Run the `when` hooks of `stage` from `dir`.
+    ///
+    /// Hooks see `INSTANT_DEV_STAGE`, `INSTANT_DEV_HOOK`, `INSTANT_DEV_CONFIG` and
+    /// `INSTANT_DEV_DRY_RUN` (`1` or `0`). They run in dry-run mode too, so they can
+    /// check the flag themselves. An error is returned only for failed hooks of a
+    /// stage set to abort; other failures are reported.
+    pub fn run(&self, stage: &str, when: When, dir: Option<&Path>) -> io::Result<()> {
+        let Some(hooks) = self.stages.get(stage) else {
+            return Ok(());
+        };
+
+        for command in hooks.commands(when) {
+            println!("Running {}-{} hook: {}", when.name(), stage, command);
+            let result = exec::run(
+                exec::shell(command, dir)
+                    .stdout(Stdio::inherit())
+                    .stderr(Stdio::inherit())
+                    .env("INSTANT_DEV_STAGE", stage)
+                    .env("INSTANT_DEV_HOOK", when.name())
+                    .env("INSTANT_DEV_CONFIG", manifest::config_path())
+                    .env(
+                        "INSTANT_DEV_DRY_RUN",
+                        if exec::is_dry_run() { "1" } else { "0" },
+                    ),
+            );
+            match (result, hooks.on_failure) {
+                (Ok(()), _) => {}
+                (Err(e), OnFailure::Abort) => {
+                    return Err(io::Error::other(format!(
+                        "{}-{} hook `{}` failed: {}",
+                        when.name(),
+                        stage,
+                        command,
+                        e
+                    )))
+                }
+                (Err(e), OnFailure::Warn) => eprintln!(
+                    "Warning: {}-{} hook `{}` failed: {}",
+                    when.name(),
+                    stage,
+                    command,
+                    e
+                ),
+            }
+        }
+        Ok(())
+    }
+}
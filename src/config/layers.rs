@@ -0,0 +1,324 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::value::Table;
+use toml::Value;
+
+use crate::config::remote::{self, RemoteConfig};
+use crate::system::path;
+
+/// Where each entry of a resolved config came from.
+///
+/// Keys are dotted paths such as `git.config.core.editor`; array items are keyed
+/// as `packages[ripgrep]`.
+pub type Sources = BTreeMap<String, String>;
+
+/// A config merged from its `extends` bases, itself and the local override file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resolved {
+    pub value: Value,
+    pub sources: Sources,
+}
+
+/// Render an array item as it is shown and keyed in [`Sources`].
+pub fn item_key(item: &Value) -> String {
+    match item {
+        Value::String(value) => value.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Join a dotted path.
+fn child(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// Record `source` for `value` at `path` and everything below it.
+fn record(value: &Value, path: &str, source: &str, sources: &mut Sources) {
+    match value {
+        Value::Table(table) => {
+            for (key, value) in table {
+                record(value, &child(path, key), source, sources);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                sources.insert(format!("{}[{}]", path, item_key(item)), source.to_string());
+            }
+        }
+        _ => {
+            sources.insert(path.to_string(), source.to_string());
+        }
+    }
+}
+
+/// Merge `overlay` into `base`.
+///
+/// Tables are merged deeply and arrays are unioned, keeping the base order. An
+/// array entry `"!name"` removes `name` instead of adding it. Any other value in
+/// `overlay` replaces the one in `base`. `source` is recorded for every entry
+/// `overlay` contributes.
+pub fn merge(base: &mut Value, overlay: &Value, path: &str, source: &str, sources: &mut Sources) {
+    match (base, overlay) {
+        (Value::Table(base), Value::Table(overlay)) => {
+            for (key, value) in overlay {
+                let path = child(path, key);
+                match base.get_mut(key) {
+                    Some(existing) => merge(existing, value, &path, source, sources),
+                    None => {
+                        let mut merged = empty_like(value);
+                        merge(&mut merged, value, &path, source, sources);
+                        base.insert(key.clone(), merged);
+                    }
+                }
+            }
+        }
+        (Value::Array(base), Value::Array(overlay)) => {
+            for item in overlay {
+                match item.as_str().and_then(|item| item.strip_prefix('!')) {
+                    Some(removed) => {
+                        base.retain(|existing| existing.as_str() != Some(removed));
+                        sources.remove(&format!("{}[{}]", path, removed));
+                    }
+                    None if base.contains(item) => {}
+                    None => {
+                        base.push(item.clone());
+                        sources.insert(format!("{}[{}]", path, item_key(item)), source.to_string());
+                    }
+                }
+            }
+        }
+        (base, overlay) => {
+            *base = overlay.clone();
+            record(overlay, path, source, sources);
+        }
+    }
+}
+
+/// An empty table or array to merge `value` into, or `value`'s own type otherwise.
+fn empty_like(value: &Value) -> Value {
+    match value {
+        Value::Table(_) => Value::Table(Table::new()),
+        Value::Array(_) => Value::Array(Vec::new()),
+        other => other.clone(),
+    }
+}
+
+/// Show `path` with `~` for the home directory.
+pub fn display_path(path: &Path) -> String {
+    match dirs::home_dir().and_then(|home| path.strip_prefix(home).ok().map(Path::to_path_buf)) {
+        Some(relative) => format!("~/{}", relative.display()),
+        None => path.display().to_string(),
+    }
+}
+
+/// A config file or URL read for resolving.
+struct Layer {
+    /// Shown as the source of the entries the layer sets.
+    label: String,
+    content: String,
+    /// Directory that relative `extends` paths in the layer are resolved against.
+    dir: PathBuf,
+}
+
+/// Read the config at `location`.
+///
+/// Relative paths are resolved against `base_dir`, the directory of the config
+/// that extends them.
+fn read_layer(location: &str, base_dir: &Path) -> Result<Layer, String> {
+    if let Some(remote) = RemoteConfig::parse(location)? {
+        let file = remote::fetch(location, &remote).map_err(|e| e.to_string())?;
+        let content = fs::read_to_string(&file)
+            .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+        return Ok(Layer {
+            label: location.to_string(),
+            content,
+            dir: file.parent().map(Path::to_path_buf).unwrap_or_default(),
+        });
+    }
+
+    let mut file = path::expand_home(location);
+    if file.is_relative() {
+        file = base_dir.join(file);
+    }
+    let content = fs::read_to_string(&file)
+        .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+    Ok(Layer {
+        label: display_path(&file),
+        content,
+        dir: file.parent().map(Path::to_path_buf).unwrap_or_default(),
+    })
+}
+
+/// Resolve the config at `location` and its `extends` chain into `resolved`.
+fn resolve_into(
+    location: &str,
+    base_dir: &Path,
+    resolved: &mut Resolved,
+    chain: &mut Vec<String>,
+) -> Result<(), String> {
+    let Layer {
+        label,
+        content,
+        dir,
+    } = read_layer(location, base_dir)?;
+    if chain.contains(&label) {
+        return Err(format!(
+            "Config extends itself: {} -> {}",
+            chain.join(" -> "),
+            label
+        ));
+    }
+    let mut config: Value =
+        toml::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", label, e))?;
+
+    chain.push(label.clone());
+    let extends = config
+        .as_table_mut()
+        .and_then(|table| table.remove("extends"));
+    let bases: Vec<String> = match &extends {
+        Some(Value::String(base)) => vec![base.clone()],
+        Some(Value::Array(bases)) => bases
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        Some(other) => {
+            return Err(format!(
+                "`extends` in {} must be a list, found {}",
+                label, other
+            ))
+        }
+        None => Vec::new(),
+    };
+    for base in bases {
+        resolve_into(&base, &dir, resolved, chain)?;
+    }
+    chain.pop();
+
+    merge(
+        &mut resolved.value,
+        &config,
+        "",
+        &label,
+        &mut resolved.sources,
+    );
+    Ok(())
+}
+
+/// Resolve `config` with its `extends` bases, then apply `local` on top if it exists.
+///
+/// Each config is merged over the ones it extends, in order, so a personal file can
+/// extend a team file and override or remove its entries.
+pub fn resolve(config: &Path, local: Option<&Path>) -> Result<Resolved, String> {
+    let mut resolved = Resolved {
+        value: Value::Table(Table::new()),
+        sources: Sources::new(),
+    };
+    let cwd = PathBuf::from(".");
+    resolve_into(
+        &config.to_string_lossy(),
+        &cwd,
+        &mut resolved,
+        &mut Vec::new(),
+    )?;
+    if let Some(local) = local.filter(|local| local.exists()) {
+        resolve_into(
+            &local.to_string_lossy(),
+            &cwd,
+            &mut resolved,
+            &mut Vec::new(),
+        )?;
+    }
+    Ok(resolved)
+}
+
+/// Quote a TOML key when it is not a bare key.
+fn render_key(key: &str) -> String {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare {
+        key.to_string()
+    } else {
+        Value::String(key.to_string()).to_string()
+    }
+}
+
+/// Look up the source of the entry at `key`.
+fn source<'a>(sources: &'a Sources, key: &str) -> &'a str {
+    sources.get(key).map(String::as_str).unwrap_or("?")
+}
+
+/// Render the table at `path` (shown as `header`) and the tables nested in it.
+fn render_table(out: &mut String, table: &Table, path: &str, header: &str, sources: &Sources) {
+    if !header.is_empty() && table.values().any(|value| !value.is_table()) {
+        out.push_str(&format!("\n[{}]\n", header));
+    }
+
+    for (key, value) in table {
+        let entry = child(path, key);
+        match value {
+            Value::Table(_) => {}
+            Value::Array(items) if items.iter().any(Value::is_table) => {}
+            Value::Array(items) => {
+                out.push_str(&format!("{} = [\n", render_key(key)));
+                for item in items {
+                    let item_source = source(sources, &format!("{}[{}]", entry, item_key(item)));
+                    out.push_str(&format!("  {}, # {}\n", item, item_source));
+                }
+                out.push_str("]\n");
+            }
+            value => {
+                let value_source = source(sources, &entry);
+                out.push_str(&format!(
+                    "{} = {} # {}\n",
+                    render_key(key),
+                    value,
+                    value_source
+                ));
+            }
+        }
+    }
+
+    // Arrays of tables come after the plain values, which would otherwise land
+    // in their last table.
+    for (key, value) in table {
+        let entry = child(path, key);
+        let Value::Array(items) = value else {
+            continue;
+        };
+        for item in items.iter().filter(|item| item.is_table()) {
+            let item_source = source(sources, &format!("{}[{}]", entry, item_key(item)));
+            out.push_str(&format!(
+                "\n[[{}]] # {}\n",
+                child(header, &render_key(key)),
+                item_source
+            ));
+            for (key, value) in item.as_table().into_iter().flatten() {
+                out.push_str(&format!("{} = {}\n", render_key(key), value));
+            }
+        }
+    }
+
+    for (key, value) in table {
+        if let Value::Table(nested) = value {
+            let header = child(header, &render_key(key));
+            render_table(out, nested, &child(path, key), &header, sources);
+        }
+    }
+}
+
+/// Render the resolved config as TOML, with the source of each entry as a comment.
+pub fn render(resolved: &Resolved) -> String {
+    let mut out = String::new();
+    if let Value::Table(root) = &resolved.value {
+        render_table(&mut out, root, "", "", &resolved.sources);
+    }
+    out
+}
@@ -0,0 +1,316 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use termion::color;
+use toml::Value;
+
+use crate::config::manifest;
+use crate::system::os::Os;
+use crate::system::settings::{self, SettingChange};
+
+/// A typed value written with `defaults write -<type>`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DefaultsValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+impl DefaultsValue {
+    /// Convert a TOML value, or `None` for arrays, tables and dates.
+    pub fn from_value(value: &Value) -> Option<DefaultsValue> {
+        match value {
+            Value::Boolean(value) => Some(DefaultsValue::Bool(*value)),
+            Value::Integer(value) => Some(DefaultsValue::Int(*value)),
+            Value::Float(value) => Some(DefaultsValue::Float(*value)),
+            Value::String(value) => Some(DefaultsValue::String(value.clone())),
+            _ => None,
+        }
+    }
+
+    /// The `defaults write` type flag for the value.
+    pub fn type_flag(&self) -> &'static str {
+        match self {
+            DefaultsValue::Bool(_) => "-bool",
+            DefaultsValue::Int(_) => "-int",
+            DefaultsValue::Float(_) => "-float",
+            DefaultsValue::String(_) => "-string",
+        }
+    }
+
+    /// Check if `current`, as printed by `defaults read`, already holds the value.
+    ///
+    /// `defaults read` prints booleans as `1` or `0`.
+    pub fn matches(&self, current: &str) -> bool {
+        match self {
+            DefaultsValue::Bool(value) => match current {
+                "1" | "true" | "YES" => *value,
+                "0" | "false" | "NO" => !*value,
+                _ => false,
+            },
+            DefaultsValue::Int(value) => current.parse::<i64>() == Ok(*value),
+            DefaultsValue::Float(value) => current.parse::<f64>() == Ok(*value),
+            DefaultsValue::String(value) => current == value,
+        }
+    }
+}
+
+impl fmt::Display for DefaultsValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DefaultsValue::Bool(value) => write!(f, "{}", value),
+            DefaultsValue::Int(value) => write!(f, "{}", value),
+            DefaultsValue::Float(value) => write!(f, "{}", value),
+            DefaultsValue::String(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// A preference from the `[macos.defaults]` tables.
+///
+/// ```toml
+/// [macos.defaults.NSGlobalDomain]
+/// KeyRepeat = 2
+/// InitialKeyRepeat = 15
+///
+/// [macos.defaults."com.apple.dock"]
+/// autohide = true
+/// tilesize = 48
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Setting {
+    pub domain: String,
+    pub key: String,
+    pub value: DefaultsValue,
+}
+
+/// A setting whose current value differs from the configured one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DefaultsChange {
+    pub setting: Setting,
+    pub current: Option<String>,
+}
+
+/// Read the `[macos.defaults]` settings from the config.
+pub fn settings(config: &Value) -> Result<Vec<Setting>, String> {
+    let Some(domains) = manifest::table(config, &["macos", "defaults"]) else {
+        return Ok(Vec::new());
+    };
+
+    let mut settings = Vec::new();
+    for (domain, keys) in domains {
+        let keys = keys
+            .as_table()
+            .ok_or_else(|| format!("[macos.defaults.\"{}\"] must be a table", domain))?;
+        for (key, value) in keys {
+            let value = DefaultsValue::from_value(value)
+                .ok_or_else(|| format!("Unsupported value for {} {}: {}", domain, key, value))?;
+            settings.push(Setting {
+                domain: domain.clone(),
+                key: key.clone(),
+                value,
+            });
+        }
+    }
+    Ok(settings)
+}
+
+/// The app to restart with `killall` after changing `domain`, if any.
+pub fn app_for_domain(domain: &str) -> Option<&'static str> {
+    match domain {
+        "com.apple.dock" => Some("Dock"),
+        "com.apple.finder" => Some("Finder"),
+        "com.apple.screencapture" | "com.apple.menuextra.clock" => Some("SystemUIServer"),
+        "com.apple.Safari" => Some("Safari"),
+        "com.apple.ActivityMonitor" => Some("Activity Monitor"),
+        _ => None,
+    }
+}
+
+/// The apps to restart for `changes`.
+pub fn affected_apps(changes: &[DefaultsChange]) -> BTreeSet<&'static str> {
+    changes
+        .iter()
+        .filter_map(|change| app_for_domain(&change.setting.domain))
+        .collect()
+}
+
+/// Reads and writes preferences through the `defaults` command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Defaults {
+    pub binary: PathBuf,
+}
+
+impl Defaults {
+    /// Use the `defaults` binary at `binary`.
+    pub fn new(binary: impl Into<PathBuf>) -> Defaults {
+        Defaults {
+            binary: binary.into(),
+        }
+    }
+
+    /// The backend for `os`, or `None` where macOS preferences are unsupported.
+    pub fn for_os(os: Os) -> Option<Defaults> {
+        match os {
+            Os::MacOs => Some(Defaults::new("defaults")),
+            Os::Linux | Os::Other => None,
+        }
+    }
+
+    /// Read the current value of `key` in `domain`.
+    pub fn read(&self, domain: &str, key: &str) -> Option<String> {
+        let output = Command::new(&self.binary)
+            .args(["read", domain, key])
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+        if output.status.success() {
+            Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        } else {
+            None
+        }
+    }
+
+    /// Write a setting with its type flag.
+    pub fn write(&self, setting: &Setting) -> io::Result<()> {
+        let output = Command::new(&self.binary)
+            .args([
+                "write",
+                &setting.domain,
+                &setting.key,
+                setting.value.type_flag(),
+                &setting.value.to_string(),
+            ])
+            .output()?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::other(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ))
+        }
+    }
+
+    /// Read back every setting and keep the ones that differ.
+    pub fn diff(&self, settings: &[Setting]) -> Vec<DefaultsChange> {
+        settings
+            .iter()
+            .filter_map(|setting| {
+                let current = self.read(&setting.domain, &setting.key);
+                match &current {
+                    Some(current) if setting.value.matches(current) => None,
+                    _ => Some(DefaultsChange {
+                        setting: setting.clone(),
+                        current,
+                    }),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Restart `app` so it picks up its new preferences.
+fn restart(app: &str) {
+    let output = match Command::new("killall")
+        .arg(app)
+        .stdout(Stdio::null())
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) => {
+            eprintln!("Error restarting {}: {}", app, e);
+            return;
+        }
+    };
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if output.status.success() {
+        println!("Restarted {}.", app);
+    } else if stderr.contains("No matching processes") {
+        // An app that is not running picks the settings up when it starts.
+        println!("{} is not running; no restart needed.", app);
+    } else if stderr.trim().is_empty() {
+        eprintln!(
+            "Error restarting {}: killall exited with {}",
+            app, output.status
+        );
+    } else {
+        eprintln!("Error restarting {}: {}", app, stderr.trim());
+    }
+}
+
+/// Configure macOS preferences.
+///
+/// This function runs when `packages.toml` has a `[macos.defaults]` section. Each value
+/// is read back with `defaults read`, and only the ones that differ are shown as a diff
+/// and written with `defaults write` and the matching type flag. Apps whose domains
+/// changed, such as the Dock or Finder, are restarted with `killall`. On other
+/// operating systems the stage reports that it is unsupported.
+pub fn configure_macos() {
+    let Some(config) = manifest::load() else {
+        return;
+    };
+    let settings = match settings(&config) {
+        Ok(settings) if settings.is_empty() => return,
+        Ok(settings) => settings,
+        Err(error) => {
+            eprintln!("Invalid [macos.defaults] configuration: {}", error);
+            return;
+        }
+    };
+
+    println!(
+        "\n{} --- Configuring macOS defaults --- {}",
+        color::Fg(color::Yellow),
+        color::Fg(color::Reset)
+    );
+
+    let os = Os::current();
+    let Some(defaults) = Defaults::for_os(os) else {
+        println!(
+            "macOS defaults are unsupported on {}. Skipping...",
+            os.name()
+        );
+        return;
+    };
+
+    let changes = defaults.diff(&settings);
+    if changes.is_empty() {
+        println!("macOS defaults are already up to date.");
+        return;
+    }
+
+    println!("Updating macOS defaults:");
+    settings::print_diff(
+        &changes
+            .iter()
+            .map(|change| SettingChange {
+                key: format!("{} {}", change.setting.domain, change.setting.key),
+                current: change.current.clone(),
+                desired: change.setting.value.to_string(),
+            })
+            .collect::<Vec<_>>(),
+    );
+
+    for change in &changes {
+        if let Err(e) = defaults.write(&change.setting) {
+            eprintln!(
+                "Error writing {} {}: {}",
+                change.setting.domain, change.setting.key, e
+            );
+        }
+    }
+
+    for app in affected_apps(&changes) {
+        restart(app);
+    }
+    if changes
+        .iter()
+        .any(|change| change.setting.domain == "NSGlobalDomain")
+    {
+        println!("Some global settings take effect after logging out and back in.");
+    }
+}
@@ -0,0 +1,119 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use toml::Value;
+
+use crate::config::layers::{self, Resolved};
+use crate::config::profiles;
+
+/// Directory holding the instant-dev configuration (`~/.config/instant-dev`).
+pub fn config_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_default()
+        .join(".config")
+        .join("instant-dev")
+}
+
+/// The config file chosen with `--config`, used instead of `packages.toml`.
+static CONFIG_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Use `path` as the configuration file for the rest of the run.
+pub fn set_config_path(path: PathBuf) {
+    let _ = CONFIG_OVERRIDE.set(path);
+}
+
+/// Path to the `packages.toml` configuration file, or the `--config` file.
+pub fn config_path() -> PathBuf {
+    CONFIG_OVERRIDE
+        .get()
+        .cloned()
+        .unwrap_or_else(|| config_dir().join("packages.toml"))
+}
+
+/// Path to the personal override file, `packages.local.toml`, applied on top of
+/// `packages.toml`.
+pub fn local_path() -> PathBuf {
+    config_dir().join("packages.local.toml")
+}
+
+/// The resolved configuration, computed once per run.
+static RESOLVED: OnceLock<Result<Option<Resolved>, String>> = OnceLock::new();
+
+/// Resolve `packages.toml` with the configs it `extends`, the local override file
+/// and the selected profiles.
+///
+/// Returns `Ok(None)` if `packages.toml` does not exist. The result is kept for
+/// the rest of the run, so remote configs are fetched only once.
+pub fn resolve() -> Result<Option<Resolved>, String> {
+    RESOLVED
+        .get_or_init(|| {
+            let path = config_path();
+            if !path.exists() {
+                return Ok(None);
+            }
+            let mut resolved = layers::resolve(&path, Some(&local_path()))?;
+            for name in profiles::apply(&mut resolved, &profiles::selected()) {
+                eprintln!("Unknown profile '{}', skipping it.", name);
+            }
+            Ok(Some(resolved))
+        })
+        .clone()
+}
+
+/// Load the resolved configuration.
+///
+/// Returns `None` if `packages.toml` does not exist. Read or parse errors are
+/// reported and also return `None`, so a broken config never aborts the run.
+pub fn load() -> Option<Value> {
+    match resolve() {
+        Ok(resolved) => resolved.map(|resolved| resolved.value),
+        Err(error) => {
+            eprintln!("{}", error);
+            None
+        }
+    }
+}
+
+/// Print `packages.toml`, or with `resolved`, the merged configuration with the
+/// source of each entry, for `instant-dev config show [--resolved]`.
+pub fn show(resolved: bool) -> io::Result<()> {
+    if !resolved {
+        print!("{}", fs::read_to_string(config_path())?);
+        return Ok(());
+    }
+
+    match resolve() {
+        Ok(Some(resolved)) => {
+            print!("{}", layers::render(&resolved));
+            Ok(())
+        }
+        Ok(None) => Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No {}", config_path().display()),
+        )),
+        Err(error) => Err(io::Error::new(io::ErrorKind::InvalidData, error)),
+    }
+}
+
+/// Look up a table nested under dotted `keys`, e.g. `["git", "config"]`.
+pub fn table<'a>(config: &'a Value, keys: &[&str]) -> Option<&'a toml::value::Table> {
+    keys.iter()
+        .try_fold(config, |value, key| value.get(key))
+        .and_then(Value::as_table)
+}
+
+/// Collect the strings of an array nested under dotted `keys`.
+pub fn strings(config: &Value, keys: &[&str]) -> Vec<String> {
+    keys.iter()
+        .try_fold(config, |value, key| value.get(key))
+        .and_then(Value::as_array)
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
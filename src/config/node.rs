@@ -0,0 +1,360 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::io;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::rc::Rc;
+use toml::Value;
+
+use crate::config::manifest;
+use crate::config::toolchain::{Tool, Toolchain};
+use crate::system::{environment, exec, path};
+
+/// The Node.js version manager used to install Node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeManager {
+    Fnm,
+    Nvm,
+}
+
+/// Node.js settings from the `[node]` table.
+///
+/// ```toml
+/// [node]
+/// manager = "fnm"          # or "nvm"
+/// versions = ["20", "22"]
+/// default = "22"           # defaults to the first version
+/// corepack = true
+/// packages = ["typescript-language-server", "@fsouza/prettierd"]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeConfig {
+    pub manager: NodeManager,
+    pub versions: Vec<String>,
+    pub default: Option<String>,
+    pub corepack: bool,
+    pub packages: Vec<String>,
+}
+
+impl NodeConfig {
+    /// Read the `[node]` table, or `None` if the Node stage is not configured.
+    pub fn from_config(config: &Value) -> Result<Option<NodeConfig>, String> {
+        let Some(node) = config.get("node") else {
+            return Ok(None);
+        };
+
+        let manager = match node.get("manager").and_then(Value::as_str) {
+            None | Some("fnm") => NodeManager::Fnm,
+            Some("nvm") => NodeManager::Nvm,
+            Some(other) => return Err(format!("Unknown Node version manager '{}'", other)),
+        };
+
+        let versions = manifest::strings(node, &["versions"]);
+        let default = node
+            .get("default")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .or_else(|| versions.first().cloned());
+
+        Ok(Some(NodeConfig {
+            manager,
+            versions,
+            default,
+            corepack: node
+                .get("corepack")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+            packages: manifest::strings(node, &["packages"]),
+        }))
+    }
+}
+
+/// Parse `npm ls --global --parseable --long` output into package names and versions.
+///
+/// Each package line looks like `<prefix>/node_modules/<name>:<name>@<version>`.
+pub fn global_packages(output: &str) -> BTreeMap<String, String> {
+    output
+        .lines()
+        .filter_map(|line| line.trim().rsplit_once("node_modules/"))
+        .filter_map(|(_, package)| {
+            let (name, id) = package.split_once(':')?;
+            let id = id.split(':').next().unwrap_or_default();
+            let version = package_version(id).unwrap_or_default();
+            Some((name.to_string(), version.to_string()))
+        })
+        .collect()
+}
+
+/// Strip a version from an npm package spec, e.g. `@scope/pkg@1.2.0`.
+pub fn package_name(spec: &str) -> &str {
+    let start = usize::from(spec.starts_with('@'));
+    match spec[start..].find('@') {
+        Some(index) => &spec[..start + index],
+        None => spec,
+    }
+}
+
+/// The version part of an npm package spec, e.g. `1.2.0` for `@scope/pkg@1.2.0`.
+pub fn package_version(spec: &str) -> Option<&str> {
+    spec.get(package_name(spec).len() + 1..)
+        .filter(|version| !version.is_empty())
+}
+
+/// Check if a global package at `installed` satisfies `spec`.
+///
+/// Exact versions such as `pkg@2.0.0` must match; specs without a version, and
+/// tags or ranges such as `pkg@latest`, only need the package to be installed.
+pub fn is_satisfied_by(spec: &str, installed: &BTreeMap<String, String>) -> bool {
+    match (installed.get(package_name(spec)), package_version(spec)) {
+        (None, _) => false,
+        (Some(current), Some(wanted)) if wanted.starts_with(|c: char| c.is_ascii_digit()) => {
+            current == wanted
+        }
+        (Some(_), _) => true,
+    }
+}
+
+/// Directory fnm is installed into when Homebrew is unavailable.
+fn fnm_dir() -> PathBuf {
+    path::expand_home("~/.local/share/fnm")
+}
+
+/// Directory nvm is installed into.
+fn nvm_dir() -> PathBuf {
+    env::var_os("NVM_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| path::expand_home("~/.nvm"))
+}
+
+/// Resolve the fnm binary from `PATH` or its install directory.
+fn fnm_bin() -> Option<PathBuf> {
+    path::which("fnm").or_else(|| Some(fnm_dir().join("fnm")).filter(|bin| bin.exists()))
+}
+
+/// Build a `bash -c <script>` command.
+fn bash(script: &str) -> Command {
+    let mut command = Command::new("bash");
+    command.arg("-c").arg(script);
+    command
+}
+
+/// Quote an argument for a bash script.
+fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+impl NodeManager {
+    /// Name of the version manager, as shown to the user.
+    pub fn name(&self) -> &'static str {
+        match self {
+            NodeManager::Fnm => "fnm",
+            NodeManager::Nvm => "nvm",
+        }
+    }
+
+    /// Check if the version manager is installed.
+    fn is_installed(&self) -> bool {
+        match self {
+            NodeManager::Fnm => fnm_bin().is_some(),
+            NodeManager::Nvm => nvm_dir().join("nvm.sh").exists(),
+        }
+    }
+
+    /// Install the version manager without touching shell startup files.
+    fn install(&self) -> io::Result<()> {
+        match self {
+            NodeManager::Fnm if path::has_binary("brew") => {
+                exec::run(&mut bash("brew install fnm"))
+            }
+            NodeManager::Fnm => exec::run(&mut bash(&format!(
+                "curl -fsSL https://fnm.vercel.app/install | bash -s -- --skip-shell --install-dir {}",
+                shell_quote(&fnm_dir().to_string_lossy())
+            ))),
+            NodeManager::Nvm => exec::run(&mut bash(
+                "curl -fsSL https://raw.githubusercontent.com/nvm-sh/nvm/v0.39.7/install.sh \
+                 | PROFILE=/dev/null bash",
+            )),
+        }
+    }
+
+    /// Add the version manager to the managed shell environment.
+    ///
+    /// The fnm install directory itself is added to `PATH` by [`NodeConfig::env_paths`].
+    fn configure_environment(&self) -> io::Result<()> {
+        match self {
+            NodeManager::Fnm => environment::add_eval("fnm env --use-on-cd"),
+            NodeManager::Nvm => {
+                environment::set_var("NVM_DIR", &nvm_dir().to_string_lossy())?;
+                environment::add_source(&nvm_dir().join("nvm.sh").to_string_lossy())
+            }
+        }
+    }
+
+    /// Build a bash script running `args` with the version manager.
+    fn script(&self, args: &[&str]) -> String {
+        let args: Vec<String> = args.iter().map(|arg| shell_quote(arg)).collect();
+        match self {
+            NodeManager::Fnm => {
+                let fnm = fnm_bin().unwrap_or_else(|| PathBuf::from("fnm"));
+                format!("{} {}", shell_quote(&fnm.to_string_lossy()), args.join(" "))
+            }
+            NodeManager::Nvm => format!(
+                ". {} && nvm {}",
+                shell_quote(&nvm_dir().join("nvm.sh").to_string_lossy()),
+                args.join(" ")
+            ),
+        }
+    }
+
+    /// Build a bash script running `command` with the Node `version` active.
+    fn exec_script(&self, version: &str, command: &[&str]) -> String {
+        match self {
+            NodeManager::Fnm => {
+                let mut args = vec!["exec", "--using", version, "--"];
+                args.extend(command);
+                self.script(&args)
+            }
+            NodeManager::Nvm => {
+                let mut args = vec!["exec", "--silent", version];
+                args.extend(command);
+                self.script(&args)
+            }
+        }
+    }
+
+    /// Install a Node version.
+    fn install_version(&self, version: &str) -> io::Result<()> {
+        exec::run(&mut bash(&self.script(&["install", version])))
+    }
+
+    /// Make `version` the default Node version.
+    fn set_default(&self, version: &str) -> io::Result<()> {
+        let args = match self {
+            NodeManager::Fnm => vec!["default", version],
+            NodeManager::Nvm => vec!["alias", "default", version],
+        };
+        exec::run(&mut bash(&self.script(&args)))
+    }
+
+    /// List the global npm packages installed for `version`.
+    fn global_packages(&self, version: &str) -> BTreeMap<String, String> {
+        bash(&self.exec_script(
+            version,
+            &[
+                "npm",
+                "ls",
+                "--global",
+                "--parseable",
+                "--long",
+                "--depth=0",
+            ],
+        ))
+        .output()
+        .map(|output| global_packages(&String::from_utf8_lossy(&output.stdout)))
+        .unwrap_or_default()
+    }
+}
+
+/// A global npm package installed for the default Node version.
+struct NpmPackage {
+    manager: NodeManager,
+    version: String,
+    spec: String,
+    installed: Rc<BTreeMap<String, String>>,
+}
+
+impl Tool for NpmPackage {
+    fn name(&self) -> String {
+        self.spec.clone()
+    }
+
+    fn is_installed(&self) -> bool {
+        is_satisfied_by(&self.spec, &self.installed)
+    }
+
+    fn install(&self) -> io::Result<()> {
+        exec::run(&mut bash(&self.manager.exec_script(
+            &self.version,
+            &["npm", "install", "--global", &self.spec],
+        )))
+    }
+}
+
+/// The Node.js toolchain, configured when `packages.toml` has a `[node]` section.
+///
+/// The version manager (fnm or nvm) is installed if needed and hooked into the managed
+/// shell environment. The declared Node versions are installed, the default version is
+/// set and corepack is optionally enabled. Global npm packages that are missing, or
+/// not at their pinned version, are installed for the default version.
+impl Toolchain for NodeConfig {
+    fn name(&self) -> &'static str {
+        "Node.js"
+    }
+
+    fn section(&self) -> &'static str {
+        "node"
+    }
+
+    fn detect(&self) -> bool {
+        self.manager.is_installed()
+    }
+
+    fn install(&self) -> io::Result<()> {
+        self.manager.install()
+    }
+
+    fn configure(&self) -> io::Result<()> {
+        self.manager.configure_environment()?;
+
+        for version in &self.versions {
+            self.manager.install_version(version)?;
+        }
+
+        let Some(default) = &self.default else {
+            println!("No Node versions configured. Skipping...");
+            return Ok(());
+        };
+        self.manager.set_default(default)?;
+
+        if self.corepack {
+            exec::run(&mut bash(
+                &self.manager.exec_script(default, &["corepack", "enable"]),
+            ))?;
+        }
+        Ok(())
+    }
+
+    fn tools(&self) -> Vec<Box<dyn Tool>> {
+        let Some(default) = &self.default else {
+            return Vec::new();
+        };
+
+        let installed = Rc::new(self.manager.global_packages(default));
+        self.packages
+            .iter()
+            .map(|spec| {
+                Box::new(NpmPackage {
+                    manager: self.manager,
+                    version: default.clone(),
+                    spec: spec.clone(),
+                    installed: Rc::clone(&installed),
+                }) as Box<dyn Tool>
+            })
+            .collect()
+    }
+
+    fn env_paths(&self) -> Vec<PathBuf> {
+        match self.manager {
+            NodeManager::Fnm if path::which("fnm").is_none() => vec![fnm_dir()],
+            _ => Vec::new(),
+        }
+    }
+
+    fn verify(&self) -> io::Result<()> {
+        let script = match &self.default {
+            Some(default) => self.manager.exec_script(default, &["node", "--version"]),
+            None => self.manager.script(&["--version"]),
+        };
+        exec::run(bash(&script).stdout(Stdio::null()))
+    }
+}
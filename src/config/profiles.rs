@@ -0,0 +1,160 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use toml::Value;
+
+use crate::config::layers::{self, Resolved};
+use crate::config::manifest;
+
+/// Profiles chosen with `--profile`, used instead of the saved default.
+static SELECTED: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Path of the file holding the default profiles (`~/.config/instant-dev/profiles`).
+pub fn saved_path() -> PathBuf {
+    manifest::config_dir().join("profiles")
+}
+
+/// Split a profile list such as `backend,data` into names.
+pub fn parse_list(list: &str) -> Vec<String> {
+    list.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Use `names` as the profiles for the rest of the run.
+pub fn select(names: Vec<String>) {
+    let _ = SELECTED.set(names);
+}
+
+/// The profiles for this run: the `--profile` ones, or else the saved default.
+pub fn selected() -> Vec<String> {
+    match SELECTED.get() {
+        Some(names) => names.clone(),
+        None => fs::read_to_string(saved_path())
+            .map(|saved| parse_list(&saved))
+            .unwrap_or_default(),
+    }
+}
+
+/// Names of the profiles under `[profiles]`.
+pub fn names(config: &Value) -> Vec<String> {
+    manifest::table(config, &["profiles"])
+        .map(|profiles| profiles.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Merge the `[profiles.<name>]` sections for `names` into the config, in order.
+///
+/// Profiles use the same rules as `extends`, so their lists add to the base
+/// config. Returns the names that have no profile.
+pub fn apply(resolved: &mut Resolved, names: &[String]) -> Vec<String> {
+    let mut unknown = Vec::new();
+    for name in names {
+        let Some(profile) = resolved
+            .value
+            .get("profiles")
+            .and_then(|profiles| profiles.get(name))
+            .cloned()
+        else {
+            unknown.push(name.clone());
+            continue;
+        };
+        layers::merge(
+            &mut resolved.value,
+            &profile,
+            "",
+            &format!("profile {}", name),
+            &mut resolved.sources,
+        );
+    }
+    unknown
+}
+
+/// Describe what a profile adds, one `key: values` line per entry.
+pub fn additions(profile: &Value) -> Vec<String> {
+    fn walk(value: &Value, path: &str, out: &mut Vec<String>) {
+        match value {
+            Value::Table(table) => {
+                for (key, value) in table {
+                    let path = if path.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{}.{}", path, key)
+                    };
+                    walk(value, &path, out);
+                }
+            }
+            Value::Array(items) => {
+                let items: Vec<String> = items.iter().map(layers::item_key).collect();
+                out.push(format!("{}: {}", path, items.join(", ")));
+            }
+            value => out.push(format!("{} = {}", path, value)),
+        }
+    }
+
+    let mut out = Vec::new();
+    walk(profile, "", &mut out);
+    out
+}
+
+/// List the profiles and what each adds, for `instant-dev profiles`.
+pub fn list() -> io::Result<()> {
+    let Some(config) = manifest::load() else {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No usable {}", manifest::config_path().display()),
+        ));
+    };
+
+    let names = names(&config);
+    if names.is_empty() {
+        println!("No [profiles] in {}.", manifest::config_path().display());
+        return Ok(());
+    }
+
+    let selected = selected();
+    for name in names {
+        let active = if selected.contains(&name) {
+            " (active)"
+        } else {
+            ""
+        };
+        println!("{}{}", name, active);
+        if let Some(profile) = config
+            .get("profiles")
+            .and_then(|profiles| profiles.get(&name))
+        {
+            for line in additions(profile) {
+                println!("  {}", line);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Save `names` as the default profiles, for `instant-dev profiles use <names>`.
+///
+/// An empty list clears the default.
+pub fn save(names: &[String]) -> io::Result<()> {
+    let path = saved_path();
+    if names.is_empty() {
+        if path.exists() {
+            fs::remove_file(&path)?;
+        }
+        println!("Cleared the default profiles.");
+        return Ok(());
+    }
+
+    if let Some(config) = manifest::load() {
+        let known = self::names(&config);
+        for name in names.iter().filter(|name| !known.contains(name)) {
+            eprintln!("Warning: no [profiles.{}] in the config.", name);
+        }
+    }
+    fs::create_dir_all(manifest::config_dir())?;
+    fs::write(&path, names.join(",") + "\n")?;
+    println!("Default profiles set to {}.", names.join(", "));
+    Ok(())
+}
@@ -0,0 +1,333 @@
+use std::env;
+use std::io;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::rc::Rc;
+use toml::Value;
+
+use crate::config::manifest;
+use crate::config::toolchain::{Tool, Toolchain};
+use crate::system::{environment, exec, path};
+
+/// The tool used to install Python versions and CLI tools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PythonManager {
+    /// uv installs Python versions and tools with `uv tool`.
+    Uv,
+    /// pyenv installs Python versions, and tools go through pipx.
+    Pyenv,
+}
+
+/// Python settings from the `[python]` table.
+///
+/// ```toml
+/// [python]
+/// manager = "uv"               # or "pyenv"
+/// versions = ["3.12", "3.11"]
+/// default = "3.12"             # defaults to the first version
+/// tools = ["ruff", "httpie"]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PythonConfig {
+    pub manager: PythonManager,
+    pub versions: Vec<String>,
+    pub default: Option<String>,
+    pub tools: Vec<String>,
+}
+
+impl PythonConfig {
+    /// Read the `[python]` table, or `None` if the Python stage is not configured.
+    pub fn from_config(config: &Value) -> Result<Option<PythonConfig>, String> {
+        let Some(python) = config.get("python") else {
+            return Ok(None);
+        };
+
+        let manager = match python.get("manager").and_then(Value::as_str) {
+            None | Some("uv") => PythonManager::Uv,
+            Some("pyenv") => PythonManager::Pyenv,
+            Some(other) => return Err(format!("Unknown Python manager '{}'", other)),
+        };
+
+        let versions = manifest::strings(python, &["versions"]);
+        let default = python
+            .get("default")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .or_else(|| versions.first().cloned());
+
+        Ok(Some(PythonConfig {
+            manager,
+            versions,
+            default,
+            tools: manifest::strings(python, &["tools"]),
+        }))
+    }
+}
+
+/// Parse `uv tool list` or `pipx list --short` output into tool names.
+pub fn installed_tools(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('-'))
+        .filter_map(|line| line.split_whitespace().next())
+        .map(str::to_string)
+        .collect()
+}
+
+/// The package name in a tool requirement such as `ruff==0.5.0` or
+/// `httpie[socks] >= 3`, normalized the way uv and pipx list it.
+pub fn tool_name(requirement: &str) -> String {
+    requirement
+        .split(|c: char| "=<>!~@[;".contains(c) || c.is_whitespace())
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+        .replace(['_', '.'], "-")
+}
+
+/// Directory holding pyenv, from `PYENV_ROOT` or `~/.pyenv`.
+fn pyenv_root() -> PathBuf {
+    env::var_os("PYENV_ROOT")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| path::expand_home("~/.pyenv"))
+}
+
+/// Directory where uv and pipx link the tools they install.
+fn user_bin_dir() -> PathBuf {
+    path::expand_home("~/.local/bin")
+}
+
+/// Resolve a binary from `PATH`, falling back to `fallback`.
+fn resolve(binary: &str, fallback: PathBuf) -> PathBuf {
+    path::which(binary).unwrap_or(fallback)
+}
+
+impl PythonManager {
+    /// Name of the manager, as shown to the user.
+    pub fn name(&self) -> &'static str {
+        match self {
+            PythonManager::Uv => "uv",
+            PythonManager::Pyenv => "pyenv",
+        }
+    }
+
+    /// Path to the manager binary.
+    fn binary(&self) -> PathBuf {
+        match self {
+            PythonManager::Uv => resolve("uv", user_bin_dir().join("uv")),
+            PythonManager::Pyenv => resolve("pyenv", pyenv_root().join("bin").join("pyenv")),
+        }
+    }
+
+    /// Check if the manager is installed.
+    fn is_installed(&self) -> bool {
+        Command::new(self.binary())
+            .arg("--version")
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    }
+
+    /// Install the manager without touching shell startup files.
+    fn install(&self) -> io::Result<()> {
+        if path::has_binary("brew") {
+            return exec::run(Command::new("brew").args(["install", self.name()]));
+        }
+
+        let script = match self {
+            PythonManager::Uv => {
+                "curl -LsSf https://astral.sh/uv/install.sh | env UV_NO_MODIFY_PATH=1 sh"
+            }
+            PythonManager::Pyenv => "curl -fsSL https://pyenv.run | bash",
+        };
+        exec::run(Command::new("sh").arg("-c").arg(script))
+    }
+
+    /// Directories holding the manager, its shims and the installed tools.
+    fn env_paths(&self) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        if let PythonManager::Pyenv = self {
+            let root = pyenv_root();
+            paths.push(root.join("bin"));
+            paths.push(root.join("shims"));
+        }
+        paths.push(user_bin_dir());
+        paths
+    }
+
+    /// Install the Python `versions`.
+    fn install_versions(&self, versions: &[String]) -> io::Result<()> {
+        let mut command = Command::new(self.binary());
+        match self {
+            PythonManager::Uv => command.args(["python", "install"]),
+            PythonManager::Pyenv => command.args(["install", "--skip-existing"]),
+        };
+        command.args(versions);
+        exec::run(&mut command)
+    }
+
+    /// Make `version` the global default Python.
+    fn set_default(&self, version: &str) -> io::Result<()> {
+        let mut command = Command::new(self.binary());
+        match self {
+            PythonManager::Uv => command.args(["python", "install", "--default", "--preview"]),
+            PythonManager::Pyenv => command.arg("global"),
+        };
+        command.arg(version);
+        exec::run(&mut command)
+    }
+
+    /// Build a command running pipx with the pyenv default Python.
+    fn pipx(&self) -> Command {
+        let mut command = Command::new(self.binary());
+        command.args(["exec", "python", "-m", "pipx"]);
+        command
+    }
+
+    /// Make sure the tool installer is available.
+    fn prepare_tools(&self) -> io::Result<()> {
+        match self {
+            PythonManager::Uv => Ok(()),
+            PythonManager::Pyenv => {
+                let has_pipx = self
+                    .pipx()
+                    .arg("--version")
+                    .output()
+                    .map(|output| output.status.success())
+                    .unwrap_or(false);
+                if has_pipx {
+                    return Ok(());
+                }
+                exec::run(
+                    Command::new(self.binary())
+                        .args(["exec", "python", "-m", "pip", "install", "--user", "pipx"]),
+                )
+            }
+        }
+    }
+
+    /// List the CLI tools already installed.
+    fn installed_tools(&self) -> Vec<String> {
+        let output = match self {
+            PythonManager::Uv => Command::new(self.binary()).args(["tool", "list"]).output(),
+            PythonManager::Pyenv => self.pipx().args(["list", "--short"]).output(),
+        };
+        output
+            .map(|output| installed_tools(&String::from_utf8_lossy(&output.stdout)))
+            .unwrap_or_default()
+    }
+
+    /// Install a CLI tool into its own environment.
+    fn install_tool(&self, tool: &str) -> io::Result<()> {
+        let mut command = match self {
+            PythonManager::Uv => {
+                let mut command = Command::new(self.binary());
+                command.args(["tool", "install"]);
+                command
+            }
+            PythonManager::Pyenv => {
+                let mut command = self.pipx();
+                command.arg("install");
+                command
+            }
+        };
+        command.arg(tool);
+        exec::run(&mut command)
+    }
+}
+
+/// A CLI tool installed into its own environment with `uv tool` or pipx.
+struct PythonTool {
+    manager: PythonManager,
+    name: String,
+    installed: Rc<Vec<String>>,
+}
+
+impl Tool for PythonTool {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn is_installed(&self) -> bool {
+        let name = tool_name(&self.name);
+        self.installed
+            .iter()
+            .any(|installed| tool_name(installed) == name)
+    }
+
+    fn install(&self) -> io::Result<()> {
+        self.manager.install_tool(&self.name)
+    }
+}
+
+/// The Python toolchain, configured when `packages.toml` has a `[python]` section.
+///
+/// uv or pyenv is installed if needed, and its shim and bin directories are added to
+/// the managed shell environment. The declared Python versions are installed and the
+/// global default is set. CLI tools are installed with `uv tool` or pipx, skipping
+/// tools that are already installed.
+impl Toolchain for PythonConfig {
+    fn name(&self) -> &'static str {
+        "Python"
+    }
+
+    fn section(&self) -> &'static str {
+        "python"
+    }
+
+    fn detect(&self) -> bool {
+        self.manager.is_installed()
+    }
+
+    fn install(&self) -> io::Result<()> {
+        self.manager.install()
+    }
+
+    fn configure(&self) -> io::Result<()> {
+        if let PythonManager::Pyenv = self.manager {
+            environment::set_var("PYENV_ROOT", &pyenv_root().to_string_lossy())?;
+        }
+        if !self.versions.is_empty() {
+            self.manager.install_versions(&self.versions)?;
+        }
+        if let Some(default) = &self.default {
+            self.manager.set_default(default)?;
+        }
+        Ok(())
+    }
+
+    fn tools(&self) -> Vec<Box<dyn Tool>> {
+        if self.tools.is_empty() {
+            return Vec::new();
+        }
+        if let Err(e) = self.manager.prepare_tools() {
+            eprintln!("Error preparing Python tools: {}", e);
+            return Vec::new();
+        }
+
+        let installed = Rc::new(self.manager.installed_tools());
+        self.tools
+            .iter()
+            .map(|name| {
+                Box::new(PythonTool {
+                    manager: self.manager,
+                    name: name.clone(),
+                    installed: Rc::clone(&installed),
+                }) as Box<dyn Tool>
+            })
+            .collect()
+    }
+
+    fn env_paths(&self) -> Vec<PathBuf> {
+        self.manager.env_paths()
+    }
+
+    fn verify(&self) -> io::Result<()> {
+        let mut command = Command::new(self.manager.binary());
+        match self.manager {
+            PythonManager::Uv => command.args(["python", "find"]),
+            PythonManager::Pyenv => command.args(["exec", "python", "--version"]),
+        };
+        exec::run(command.stdout(Stdio::null()))
+    }
+}
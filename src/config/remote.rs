@@ -0,0 +1,330 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A config fetched from a git repo or URL instead of read from disk.
+///
+/// ```text
+/// git+ssh://git@github.com/acme/setup.git#team/packages.toml
+/// git+https://github.com/acme/setup.git?ref=v2#packages.toml
+/// https://example.com/packages.toml
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoteConfig {
+    Git {
+        /// URL passed to `git clone`, without the `git+` prefix.
+        url: String,
+        /// Branch, tag or commit to pin to, instead of the default branch.
+        reference: Option<String>,
+        /// Path of the config file inside the repo.
+        path: String,
+    },
+    Https {
+        url: String,
+    },
+}
+
+/// How a remote config compares with the cached copy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FetchStatus {
+    /// Nothing was cached yet.
+    New,
+    Unchanged,
+    /// The config changed since the cached copy, which was at commit `from` for git.
+    Updated {
+        from: Option<String>,
+    },
+    /// Fetching failed, so the cached copy is used.
+    Offline {
+        error: String,
+    },
+}
+
+/// A remote config in the local cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FetchedConfig {
+    /// The cached config file.
+    pub path: PathBuf,
+    /// The commit the config was read from, for git.
+    pub commit: Option<String>,
+    pub status: FetchStatus,
+}
+
+/// Directory holding cached remote configs (`~/.cache/instant-dev/config`).
+pub fn cache_root() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(|| dirs::home_dir().unwrap_or_default().join(".cache"))
+        .join("instant-dev")
+        .join("config")
+}
+
+/// Run `command` and return its trimmed stdout, or its stderr as the error.
+fn output(command: &mut Command) -> io::Result<String> {
+    let output = command.output()?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+/// The first line of an error, for one-line warnings.
+fn first_line(error: &io::Error) -> String {
+    error
+        .to_string()
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Build a `git -C <checkout>` command.
+fn git(checkout: &Path) -> Command {
+    let mut command = Command::new("git");
+    command.arg("-C").arg(checkout);
+    command
+}
+
+/// Shorten a commit hash for display.
+pub fn short(commit: &str) -> &str {
+    &commit[..commit.len().min(7)]
+}
+
+impl RemoteConfig {
+    /// Parse a `--config` or `extends` location, or `None` for a local path.
+    ///
+    /// Git locations start with `git+` and name the file after `#`, defaulting to
+    /// `packages.toml`; `?ref=` pins a branch, tag or commit. Other URLs must use
+    /// `https://`, so configs are never fetched over plain HTTP.
+    pub fn parse(location: &str) -> Result<Option<RemoteConfig>, String> {
+        if let Some(rest) = location.strip_prefix("git+") {
+            let (rest, path) = rest.split_once('#').unwrap_or((rest, "packages.toml"));
+            let (url, reference) = match rest.split_once("?ref=") {
+                Some((url, reference)) => (url, Some(reference.to_string())),
+                None => (rest, None),
+            };
+            return Ok(Some(RemoteConfig::Git {
+                url: url.to_string(),
+                reference,
+                path: path.to_string(),
+            }));
+        }
+        if location.starts_with("https://") {
+            return Ok(Some(RemoteConfig::Https {
+                url: location.to_string(),
+            }));
+        }
+        if location.contains("://") {
+            return Err(format!(
+                "Unsupported config location {}: use an https:// or git+ URL",
+                location
+            ));
+        }
+        Ok(None)
+    }
+
+    /// Directory caching this config under `cache_root`, named after its location.
+    pub fn cache_dir(&self, cache_root: &Path) -> PathBuf {
+        let location = match self {
+            RemoteConfig::Git { url, reference, .. } => match reference {
+                Some(reference) => format!("{}-{}", url, reference),
+                None => url.clone(),
+            },
+            RemoteConfig::Https { url } => url.clone(),
+        };
+        let name: String = location
+            .split_once("://")
+            .map_or(location.as_str(), |(_, rest)| rest)
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '.' {
+                    c
+                } else {
+                    '-'
+                }
+            })
+            .collect();
+        cache_root.join(name.trim_matches('-'))
+    }
+
+    /// Fetch the config into `cache_root`, falling back to the cached copy when
+    /// the remote cannot be reached.
+    pub fn fetch(&self, cache_root: &Path) -> io::Result<FetchedConfig> {
+        let dir = self.cache_dir(cache_root);
+        match self {
+            RemoteConfig::Git {
+                url,
+                reference,
+                path,
+            } => fetch_git(url, reference.as_deref(), &dir.join("repo"), path),
+            RemoteConfig::Https { url } => fetch_https(url, &dir.join("packages.toml")),
+        }
+    }
+}
+
+/// Clone or fetch `url` into `checkout` and check out `reference`, or the
+/// default branch.
+fn fetch_git(
+    url: &str,
+    reference: Option<&str>,
+    checkout: &Path,
+    path: &str,
+) -> io::Result<FetchedConfig> {
+    let cached = if checkout.join(".git").exists() {
+        Some(output(git(checkout).args(["rev-parse", "HEAD"]))?)
+    } else {
+        None
+    };
+
+    let fetched = match &cached {
+        Some(_) => output(git(checkout).args(["fetch", "--quiet", "--tags", "origin"])),
+        None => {
+            if let Some(parent) = checkout.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            output(
+                Command::new("git")
+                    .args(["clone", "--quiet", "--no-checkout", url])
+                    .arg(checkout),
+            )
+        }
+    };
+
+    let status = match (fetched, &cached) {
+        (Ok(_), _) => {
+            let target = match reference {
+                Some(reference) => output(
+                    git(checkout)
+                        .args(["rev-parse", "--verify", "--quiet"])
+                        .arg(format!("refs/remotes/origin/{}^{{commit}}", reference)),
+                )
+                .or_else(|_| {
+                    output(
+                        git(checkout)
+                            .args(["rev-parse", "--verify", "--quiet"])
+                            .arg(format!("{}^{{commit}}", reference)),
+                    )
+                })
+                .map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("No ref {} in {}", reference, url),
+                    )
+                })?,
+                None => output(git(checkout).args(["rev-parse", "refs/remotes/origin/HEAD"]))?,
+            };
+            output(
+                git(checkout)
+                    .args(["checkout", "--quiet", "--force", "--detach"])
+                    .arg(&target),
+            )?;
+            match cached {
+                None => FetchStatus::New,
+                Some(from) if from == target => FetchStatus::Unchanged,
+                Some(from) => FetchStatus::Updated { from: Some(from) },
+            }
+        }
+        (Err(error), Some(_)) => FetchStatus::Offline {
+            error: first_line(&error),
+        },
+        (Err(error), None) => {
+            let _ = fs::remove_dir_all(checkout);
+            return Err(io::Error::other(format!(
+                "Failed to clone {}: {}",
+                url, error
+            )));
+        }
+    };
+
+    let config = checkout.join(path);
+    if !config.is_file() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No {} in {}", path, url),
+        ));
+    }
+    Ok(FetchedConfig {
+        path: config,
+        commit: Some(output(git(checkout).args(["rev-parse", "HEAD"]))?),
+        status,
+    })
+}
+
+/// Download `url` to `cached`, keeping the previous copy when it fails.
+fn fetch_https(url: &str, cached: &Path) -> io::Result<FetchedConfig> {
+    if let Some(parent) = cached.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let previous = fs::read_to_string(cached).ok();
+
+    let status = match output(Command::new("curl").args(["-fsSL", url])) {
+        Ok(content) => {
+            fs::write(cached, &content)?;
+            match previous {
+                None => FetchStatus::New,
+                Some(previous) if previous.trim() == content => FetchStatus::Unchanged,
+                Some(_) => FetchStatus::Updated { from: None },
+            }
+        }
+        Err(error) if previous.is_some() => FetchStatus::Offline {
+            error: first_line(&error),
+        },
+        Err(error) => {
+            return Err(io::Error::other(format!(
+                "Failed to fetch {}: {}",
+                url, error
+            )))
+        }
+    };
+
+    Ok(FetchedConfig {
+        path: cached.to_path_buf(),
+        commit: None,
+        status,
+    })
+}
+
+/// Fetch the remote config at `location` into the cache and report how it
+/// compares with the cached copy.
+///
+/// Returns the path of the cached config file.
+pub fn fetch(location: &str, remote: &RemoteConfig) -> io::Result<PathBuf> {
+    let fetched = remote.fetch(&cache_root())?;
+    let at = fetched
+        .commit
+        .as_deref()
+        .map(|commit| format!(" at {}", short(commit)))
+        .unwrap_or_default();
+
+    match &fetched.status {
+        FetchStatus::New => println!("Fetched config from {}{}.", location, at),
+        FetchStatus::Unchanged => {}
+        FetchStatus::Updated { from: Some(from) } => println!(
+            "Config from {} updated from {}{}.",
+            location,
+            short(from),
+            at
+        ),
+        FetchStatus::Updated { from: None } => println!("Config from {} updated.", location),
+        FetchStatus::Offline { error } => eprintln!(
+            "Could not fetch {} ({}). Using the cached copy{}.",
+            location, error, at
+        ),
+    }
+    Ok(fetched.path)
+}
+
+/// Use the config at `location` for this run, for `--config <location>`.
+///
+/// Remote configs are fetched into the cache first; local paths are used as is.
+pub fn config_path(location: &str) -> io::Result<PathBuf> {
+    match RemoteConfig::parse(location)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?
+    {
+        Some(remote) => fetch(location, &remote),
+        None => Ok(crate::system::path::expand_home(location)),
+    }
+}
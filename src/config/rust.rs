@@ -0,0 +1,271 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::io;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::rc::Rc;
+use toml::Value;
+
+use crate::config::manifest;
+use crate::config::toolchain::{Tool, Toolchain};
+use crate::system::{exec, path};
+
+/// Rust toolchain settings from the `[rust]` table.
+///
+/// ```toml
+/// [rust]
+/// toolchain = "stable"
+/// components = ["clippy", "rustfmt", "rust-analyzer", "rust-src"]
+/// targets = ["wasm32-unknown-unknown"]
+/// tools = ["cargo-watch", "cargo-nextest@0.9.70"]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RustConfig {
+    pub toolchain: String,
+    pub components: Vec<String>,
+    pub targets: Vec<String>,
+    pub tools: Vec<CargoTool>,
+}
+
+impl RustConfig {
+    /// Read the `[rust]` table, or `None` if the Rust stage is not configured.
+    pub fn from_config(config: &Value) -> Option<RustConfig> {
+        let rust = config.get("rust")?;
+
+        Some(RustConfig {
+            toolchain: rust
+                .get("toolchain")
+                .and_then(Value::as_str)
+                .unwrap_or("stable")
+                .to_string(),
+            components: manifest::strings(rust, &["components"]),
+            targets: manifest::strings(rust, &["targets"]),
+            tools: rust
+                .get("tools")
+                .and_then(Value::as_array)
+                .map(|tools| {
+                    tools
+                        .iter()
+                        .filter_map(|value| {
+                            let tool = CargoTool::from_value(value);
+                            if tool.is_none() {
+                                eprintln!("Invalid Rust tool entry: {}", value);
+                            }
+                            tool
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+    }
+}
+
+/// A binary crate installed with `cargo install`.
+///
+/// Declared as `name`, `name@version`, or `{ name = "...", version = "..." }`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CargoTool {
+    pub name: String,
+    pub version: Option<String>,
+}
+
+impl CargoTool {
+    /// Parse a tool entry from the `[rust] tools` list.
+    pub fn from_value(value: &Value) -> Option<CargoTool> {
+        let (name, version) = match value {
+            Value::String(spec) => match spec.split_once('@') {
+                Some((name, version)) => (name, Some(version)),
+                None => (spec.as_str(), None),
+            },
+            Value::Table(table) => (
+                table.get("name").and_then(Value::as_str)?,
+                table.get("version").and_then(Value::as_str),
+            ),
+            _ => return None,
+        };
+
+        if name.is_empty() {
+            return None;
+        }
+        Some(CargoTool {
+            name: name.to_string(),
+            version: version.map(|version| version.trim_start_matches('v').to_string()),
+        })
+    }
+
+    /// Check if the tool is installed at the requested version.
+    ///
+    /// Tools without a pinned version only need to be installed.
+    pub fn is_satisfied_by(&self, installed: &BTreeMap<String, String>) -> bool {
+        match (installed.get(&self.name), &self.version) {
+            (Some(_), None) => true,
+            (Some(current), Some(wanted)) => current == wanted,
+            (None, _) => false,
+        }
+    }
+}
+
+/// Parse the output of `cargo install --list` into crate names and versions.
+pub fn installed_crates(output: &str) -> BTreeMap<String, String> {
+    output
+        .lines()
+        .filter(|line| !line.starts_with(char::is_whitespace))
+        .filter_map(|line| {
+            let mut fields = line.trim_end_matches(':').split_whitespace();
+            let name = fields.next()?;
+            let version = fields.next()?.trim_start_matches('v');
+            Some((name.to_string(), version.to_string()))
+        })
+        .collect()
+}
+
+/// The cargo home directory, where rustup installs its binaries.
+fn cargo_home() -> PathBuf {
+    env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| path::expand_home("~/.cargo"))
+}
+
+/// Resolve a binary from `CARGO_HOME/bin`, falling back to `PATH`.
+fn cargo_bin(binary: &str) -> PathBuf {
+    let installed = cargo_home().join("bin").join(binary);
+    if installed.exists() {
+        installed
+    } else {
+        PathBuf::from(binary)
+    }
+}
+
+/// Check if rustup is installed.
+fn check_rustup_installed() -> bool {
+    Command::new(cargo_bin("rustup"))
+        .arg("--version")
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Install rustup with the official installer, without Homebrew.
+///
+/// The installer is told not to edit shell startup files, since `CARGO_HOME/bin`
+/// is added to the managed shell environment instead.
+fn install_rustup() -> io::Result<()> {
+    exec::run(Command::new("sh").arg("-c").arg(
+        "curl --proto '=https' --tlsv1.2 -sSf https://sh.rustup.rs \
+         | sh -s -- -y --no-modify-path --default-toolchain none",
+    ))
+}
+
+/// Run rustup with `args`.
+fn rustup(args: &[&str]) -> io::Result<()> {
+    exec::run(Command::new(cargo_bin("rustup")).args(args))
+        .map_err(|e| io::Error::other(format!("rustup {}: {}", args.join(" "), e)))
+}
+
+/// A [`CargoTool`] checked against the crates from `cargo install --list`.
+struct CargoToolInstall {
+    tool: CargoTool,
+    installed: Rc<BTreeMap<String, String>>,
+}
+
+impl Tool for CargoToolInstall {
+    fn name(&self) -> String {
+        self.tool.name.clone()
+    }
+
+    fn is_installed(&self) -> bool {
+        self.tool.is_satisfied_by(&self.installed)
+    }
+
+    fn install(&self) -> io::Result<()> {
+        let mut command = Command::new(cargo_bin("cargo"));
+        command.args(["install", "--locked", &self.tool.name]);
+        if let Some(version) = &self.tool.version {
+            command.args(["--version", version]);
+        }
+        exec::run(&mut command)
+    }
+}
+
+/// The Rust toolchain, configured when `packages.toml` has a `[rust]` section.
+///
+/// rustup is installed with the official installer if needed, and `CARGO_HOME/bin`
+/// is added to the managed shell environment. The default toolchain is set and the
+/// declared components and targets are added. Tools are installed with
+/// `cargo install`, skipping any already installed at the requested version.
+impl Toolchain for RustConfig {
+    fn name(&self) -> &'static str {
+        "Rust"
+    }
+
+    fn section(&self) -> &'static str {
+        "rust"
+    }
+
+    fn detect(&self) -> bool {
+        check_rustup_installed()
+    }
+
+    fn install(&self) -> io::Result<()> {
+        install_rustup()
+    }
+
+    fn configure(&self) -> io::Result<()> {
+        rustup(&[
+            "toolchain",
+            "install",
+            &self.toolchain,
+            "--profile",
+            "minimal",
+        ])?;
+        rustup(&["default", &self.toolchain])?;
+
+        if !self.components.is_empty() {
+            let mut args = vec!["component", "add", "--toolchain", &self.toolchain];
+            args.extend(self.components.iter().map(String::as_str));
+            rustup(&args)?;
+        }
+
+        if !self.targets.is_empty() {
+            let mut args = vec!["target", "add", "--toolchain", &self.toolchain];
+            args.extend(self.targets.iter().map(String::as_str));
+            rustup(&args)?;
+        }
+        Ok(())
+    }
+
+    fn tools(&self) -> Vec<Box<dyn Tool>> {
+        if self.tools.is_empty() {
+            return Vec::new();
+        }
+
+        let installed = Rc::new(
+            Command::new(cargo_bin("cargo"))
+                .args(["install", "--list"])
+                .output()
+                .map(|output| installed_crates(&String::from_utf8_lossy(&output.stdout)))
+                .unwrap_or_default(),
+        );
+        self.tools
+            .iter()
+            .map(|tool| {
+                Box::new(CargoToolInstall {
+                    tool: tool.clone(),
+                    installed: Rc::clone(&installed),
+                }) as Box<dyn Tool>
+            })
+            .collect()
+    }
+
+    fn env_paths(&self) -> Vec<PathBuf> {
+        vec![cargo_home().join("bin")]
+    }
+
+    fn verify(&self) -> io::Result<()> {
+        exec::run(
+            Command::new(cargo_bin("rustc"))
+                .arg("--version")
+                .stdout(Stdio::null()),
+        )
+    }
+}
@@ -0,0 +1,284 @@
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Stdio};
+use termion::color;
+use toml::Value;
+
+use crate::config::{gitconfig, manifest};
+use crate::system::path;
+
+/// How commits are signed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SigningFormat {
+    Ssh,
+    Gpg,
+}
+
+/// Commit signing settings from the `[git.signing]` table.
+///
+/// ```toml
+/// [git.signing]
+/// format = "ssh"                 # or "gpg"
+/// key = "~/.ssh/id_ed25519.pub"  # public key path, or GPG key id
+/// upload = true                  # add the key to GitHub as a signing key
+/// verify = true                  # make a signed test commit afterwards
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signing {
+    pub format: SigningFormat,
+    pub key: String,
+    pub upload: bool,
+    pub verify: bool,
+}
+
+impl Signing {
+    /// Read the signing settings, or `None` if `[git.signing]` is absent.
+    pub fn from_config(config: &Value) -> Result<Option<Signing>, String> {
+        let Some(table) = manifest::table(config, &["git", "signing"]) else {
+            return Ok(None);
+        };
+
+        let format = match table.get("format").and_then(Value::as_str) {
+            None | Some("ssh") => SigningFormat::Ssh,
+            Some("gpg") | Some("openpgp") => SigningFormat::Gpg,
+            Some(other) => return Err(format!("Unknown signing format '{}'", other)),
+        };
+
+        let key = match (table.get("key").and_then(Value::as_str), &format) {
+            (Some(key), _) => key.to_string(),
+            (None, SigningFormat::Ssh) => "~/.ssh/id_ed25519.pub".to_string(),
+            (None, SigningFormat::Gpg) => {
+                return Err("GPG signing requires a 'key' id".to_string());
+            }
+        };
+
+        let flag =
+            |name: &str, default: bool| table.get(name).and_then(Value::as_bool).unwrap_or(default);
+
+        Ok(Some(Signing {
+            format,
+            key,
+            upload: flag("upload", false),
+            verify: flag("verify", true),
+        }))
+    }
+}
+
+/// Path to the `allowed_signers` file used to verify SSH signatures.
+pub fn allowed_signers_path() -> PathBuf {
+    path::expand_home("~/.config/git/allowed_signers")
+}
+
+/// Add `public_key` for `email` to the contents of an `allowed_signers` file.
+///
+/// Existing entries are kept, and the key is only added once.
+pub fn merge_allowed_signers(existing: &str, email: &str, public_key: &str) -> String {
+    let entry = format!("{} namespaces=\"git\" {}", email, public_key.trim());
+    let key_blob = public_key
+        .split_whitespace()
+        .take(2)
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut lines: Vec<String> = existing
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(str::to_string)
+        .collect();
+
+    let already_present = lines
+        .iter()
+        .any(|line| line.starts_with(&format!("{} ", email)) && line.contains(&key_blob));
+
+    if !already_present {
+        lines.push(entry);
+    }
+
+    let mut merged = lines.join("\n");
+    merged.push('\n');
+    merged
+}
+
+/// Configure git to sign with the SSH public key at `key_path`.
+fn configure_ssh_signing(key_path: &Path, email: &str) -> io::Result<()> {
+    let public_key = fs::read_to_string(key_path)?;
+
+    let signers_path = allowed_signers_path();
+    if let Some(parent) = signers_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let existing = fs::read_to_string(&signers_path).unwrap_or_default();
+    fs::write(
+        &signers_path,
+        merge_allowed_signers(&existing, email, &public_key),
+    )?;
+
+    gitconfig::set_global("gpg.format", "ssh")?;
+    gitconfig::set_global("user.signingkey", &key_path.to_string_lossy())?;
+    gitconfig::set_global(
+        "gpg.ssh.allowedSignersFile",
+        &signers_path.to_string_lossy(),
+    )?;
+    gitconfig::set_global("commit.gpgsign", "true")
+}
+
+/// Configure git to sign with the GPG key `key_id`.
+fn configure_gpg_signing(key_id: &str) -> io::Result<()> {
+    gitconfig::set_global("gpg.format", "openpgp")?;
+    gitconfig::set_global("user.signingkey", key_id)?;
+    gitconfig::set_global("commit.gpgsign", "true")
+}
+
+/// Upload the signing key to GitHub with the `gh` CLI.
+fn upload_signing_key(signing: &Signing) -> io::Result<()> {
+    if !path::has_binary("gh") {
+        return Err(io::Error::other(
+            "the GitHub CLI (gh) is not installed; add the key at https://github.com/settings/keys",
+        ));
+    }
+
+    let output = match signing.format {
+        SigningFormat::Ssh => Command::new("gh")
+            .args([
+                "ssh-key",
+                "add",
+                "--type",
+                "signing",
+                "--title",
+                "instant-dev",
+            ])
+            .arg(path::expand_home(&signing.key))
+            .output()?,
+        SigningFormat::Gpg => {
+            let export = Command::new("gpg")
+                .args(["--armor", "--export", &signing.key])
+                .output()?;
+            if !export.status.success() {
+                return Err(io::Error::other(format!(
+                    "gpg --export failed: {}",
+                    String::from_utf8_lossy(&export.stderr).trim()
+                )));
+            }
+            if export.stdout.iter().all(u8::is_ascii_whitespace) {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("gpg has no public key for {}", signing.key),
+                ));
+            }
+            let mut upload = Command::new("gh")
+                .args(["gpg-key", "add", "-"])
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()?;
+            if let Some(mut stdin) = upload.stdin.take() {
+                stdin.write_all(&export.stdout)?;
+            }
+            upload.wait_with_output()?
+        }
+    };
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if output.status.success() || stderr.contains("already") {
+        Ok(())
+    } else {
+        Err(io::Error::other(stderr.trim().to_string()))
+    }
+}
+
+/// Run git in `dir` and fail if it exits unsuccessfully.
+fn git_in(dir: &Path, args: &[&str]) -> io::Result<()> {
+    let output = Command::new("git").current_dir(dir).args(args).output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+/// Make a signed commit in a temporary repository and verify its signature.
+pub fn verify_signing() -> io::Result<()> {
+    let dir = env::temp_dir().join(format!("instant-dev-signing-{}", process::id()));
+    fs::create_dir_all(&dir)?;
+
+    let result = git_in(&dir, &["init", "--quiet"])
+        .and_then(|_| {
+            git_in(
+                &dir,
+                &[
+                    "commit",
+                    "--quiet",
+                    "--allow-empty",
+                    "--gpg-sign",
+                    "-m",
+                    "instant-dev signing test",
+                ],
+            )
+        })
+        .and_then(|_| git_in(&dir, &["verify-commit", "HEAD"]));
+
+    let _ = fs::remove_dir_all(&dir);
+    result
+}
+
+/// Set up commit signing as declared in `[git.signing]`.
+///
+/// # Arguments
+///
+/// * `verbose` - A boolean indicating whether to print verbose output.
+pub fn configure_signing(verbose: bool) {
+    let Some(config) = manifest::load() else {
+        return;
+    };
+
+    let signing = match Signing::from_config(&config) {
+        Ok(Some(signing)) => signing,
+        Ok(None) => return,
+        Err(error) => {
+            eprintln!("Invalid [git.signing] configuration: {}", error);
+            return;
+        }
+    };
+
+    println!(
+        "\n{} --- Configuring commit signing --- {}",
+        color::Fg(color::Yellow),
+        color::Fg(color::Reset)
+    );
+
+    let result = match signing.format {
+        SigningFormat::Ssh => match gitconfig::get_global("user.email") {
+            Some(email) => configure_ssh_signing(&path::expand_home(&signing.key), &email),
+            None => Err(io::Error::other("user.email is not set")),
+        },
+        SigningFormat::Gpg => configure_gpg_signing(&signing.key),
+    };
+
+    if let Err(error) = result {
+        eprintln!("Error configuring commit signing: {}", error);
+        return;
+    }
+    println!("Commit signing configured.");
+
+    if signing.upload {
+        match upload_signing_key(&signing) {
+            Ok(()) => println!("Signing key uploaded to GitHub."),
+            Err(error) => eprintln!("Error uploading signing key: {}", error),
+        }
+    }
+
+    if signing.verify {
+        match verify_signing() {
+            Ok(()) => println!("Signed test commit verified successfully."),
+            Err(error) => eprintln!("Signed test commit failed: {}", error),
+        }
+    } else if verbose {
+        println!("Skipping signed test commit.");
+    }
+}
@@ -0,0 +1,299 @@
+use std::collections::BTreeMap;
+use std::process::Command;
+use termion::color;
+use toml::value::Table;
+use toml::Value;
+
+use crate::config::{gitconfig, go, manifest};
+use crate::system::package_manager::PackageManager;
+use crate::system::path;
+
+/// Language servers recorded under `lsp` when they are on `PATH`.
+const LANGUAGE_SERVERS: [&str; 14] = [
+    "rust-analyzer",
+    "gopls",
+    "typescript-language-server",
+    "pyright",
+    "pylsp",
+    "lua-language-server",
+    "clangd",
+    "taplo",
+    "yaml-language-server",
+    "bash-language-server",
+    "vscode-json-language-server",
+    "marksman",
+    "terraform-ls",
+    "lldb-vscode",
+];
+
+/// Taps every Homebrew install has, left out of the snapshot.
+const DEFAULT_TAPS: [&str; 2] = ["homebrew/core", "homebrew/cask"];
+
+/// Split `git config --global --list` output into `[git.config]` settings and
+/// `[git.aliases]`.
+///
+/// Identity and include settings are personal, so `user.*`, `include.*` and
+/// `includeif.*` keys are left out.
+pub fn git_settings(output: &str) -> (BTreeMap<String, String>, BTreeMap<String, String>) {
+    let mut settings = BTreeMap::new();
+    let mut aliases = BTreeMap::new();
+
+    for (key, value) in output.lines().filter_map(|line| line.split_once('=')) {
+        if let Some(alias) = key.strip_prefix("alias.") {
+            aliases.insert(alias.to_string(), value.to_string());
+        } else if !["user.", "include.", "includeif."]
+            .iter()
+            .any(|prefix| key.starts_with(prefix))
+        {
+            settings.insert(key.to_string(), value.to_string());
+        }
+    }
+    (settings, aliases)
+}
+
+/// What was found on the machine, before it is turned into a config.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    pub taps: Vec<String>,
+    pub packages: Vec<String>,
+    pub casks: Vec<String>,
+    pub lsp: Vec<String>,
+    pub go_tools: Vec<go::GoTool>,
+    pub git_config: BTreeMap<String, String>,
+    pub git_aliases: BTreeMap<String, String>,
+}
+
+/// Convert strings to a TOML array.
+fn array(values: &[String]) -> Value {
+    Value::Array(values.iter().cloned().map(Value::String).collect())
+}
+
+/// Convert a string map to a TOML table.
+fn table(values: &BTreeMap<String, String>) -> Value {
+    Value::Table(
+        values
+            .iter()
+            .map(|(key, value)| (key.clone(), Value::String(value.clone())))
+            .collect(),
+    )
+}
+
+impl Snapshot {
+    /// Inspect the machine through `manager`, git and Go.
+    pub fn capture(manager: &dyn PackageManager) -> Snapshot {
+        let git_list = Command::new("git")
+            .args(["config", "--global", "--list"])
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+            .unwrap_or_default();
+        let (git_config, git_aliases) = git_settings(&git_list);
+
+        Snapshot {
+            taps: manager
+                .installed_taps()
+                .into_iter()
+                .filter(|tap| !DEFAULT_TAPS.contains(&tap.as_str()))
+                .collect(),
+            packages: manager.leaves(),
+            casks: manager.installed_casks(),
+            lsp: LANGUAGE_SERVERS
+                .iter()
+                .filter(|server| path::has_binary(server))
+                .map(|server| server.to_string())
+                .collect(),
+            go_tools: go::installed_tools(),
+            git_config,
+            git_aliases,
+        }
+    }
+
+    /// Build the packages.toml for the snapshot, leaving out empty sections.
+    pub fn to_config(&self) -> Value {
+        let mut config = Table::new();
+        for (key, values) in [
+            ("taps", &self.taps),
+            ("packages", &self.packages),
+            ("casks", &self.casks),
+            ("lsp", &self.lsp),
+        ] {
+            if !values.is_empty() {
+                config.insert(key.to_string(), array(values));
+            }
+        }
+
+        if !self.go_tools.is_empty() {
+            let tools: Vec<String> = self
+                .go_tools
+                .iter()
+                .map(go::GoTool::install_target)
+                .collect();
+            let mut go = Table::new();
+            go.insert("tools".to_string(), array(&tools));
+            config.insert("go".to_string(), Value::Table(go));
+        }
+
+        let mut git = Table::new();
+        if !self.git_config.is_empty() {
+            git.insert("config".to_string(), table(&self.git_config));
+        }
+        if !self.git_aliases.is_empty() {
+            git.insert("aliases".to_string(), table(&self.git_aliases));
+        }
+        if !git.is_empty() {
+            config.insert("git".to_string(), Value::Table(git));
+        }
+
+        Value::Table(config)
+    }
+}
+
+/// An entry the snapshot has that the config lacks or sets differently.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotDiff {
+    /// Dotted path of the entry, e.g. `packages` or `git.config.core.editor`.
+    pub key: String,
+    pub value: String,
+    /// The configured value, when the config sets the key differently.
+    pub configured: Option<String>,
+}
+
+/// Render a TOML value without quotes.
+fn display(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Compare the array `key` item by item; Go tools match on their package.
+fn diff_array(key: &str, snapshot: &[Value], config: Option<&Value>) -> Vec<SnapshotDiff> {
+    let configured: Vec<String> = match config {
+        Some(Value::Array(items)) if key == "go.tools" => items
+            .iter()
+            .filter_map(go::GoTool::from_value)
+            .map(|tool| tool.package)
+            .collect(),
+        Some(Value::Array(items)) => items.iter().map(display).collect(),
+        _ => Vec::new(),
+    };
+
+    snapshot
+        .iter()
+        .map(display)
+        .filter(|item| {
+            let name = match key {
+                "go.tools" => item.split('@').next().unwrap_or(item),
+                _ => item,
+            };
+            !configured.iter().any(|configured| configured == name)
+        })
+        .map(|item| SnapshotDiff {
+            key: key.to_string(),
+            value: item,
+            configured: None,
+        })
+        .collect()
+}
+
+/// Compare the snapshot's `[git.config]` and `[git.aliases]` with the settings
+/// the config applies, including its preset.
+///
+/// `git config --list` lowercases section and key names, so keys match
+/// case-insensitively.
+fn diff_git(git: &Value, config: &Value) -> Vec<SnapshotDiff> {
+    let desired: BTreeMap<String, String> = gitconfig::desired_settings(config)
+        .into_iter()
+        .map(|(key, value)| (key.to_lowercase(), value))
+        .collect();
+
+    let mut out = Vec::new();
+    for (section, prefix) in [("config", ""), ("aliases", "alias.")] {
+        let Some(entries) = git.get(section).and_then(Value::as_table) else {
+            continue;
+        };
+        for (key, value) in entries {
+            let value = display(value);
+            let configured = desired.get(&format!("{}{}", prefix, key).to_lowercase());
+            if configured != Some(&value) {
+                out.push(SnapshotDiff {
+                    key: format!("git.{}.{}", section, key),
+                    value,
+                    configured: configured.cloned(),
+                });
+            }
+        }
+    }
+    out
+}
+
+/// List what `snapshot` has that `config` lacks or sets differently.
+pub fn diff(snapshot: &Value, config: &Value) -> Vec<SnapshotDiff> {
+    let mut out = Vec::new();
+    let Some(entries) = snapshot.as_table() else {
+        return out;
+    };
+
+    for (key, value) in entries {
+        match (key.as_str(), value) {
+            ("git", git) => out.extend(diff_git(git, config)),
+            ("go", go) => {
+                if let Some(Value::Array(tools)) = go.get("tools") {
+                    let configured = config.get("go").and_then(|go| go.get("tools"));
+                    out.extend(diff_array("go.tools", tools, configured));
+                }
+            }
+            (_, Value::Array(items)) => out.extend(diff_array(key, items, config.get(key))),
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Print the snapshot as packages.toml, or with `show_diff`, what the team
+/// config lacks, for `instant-dev snapshot [--diff]`.
+pub fn snapshot(manager: &dyn PackageManager, show_diff: bool) {
+    let config = Snapshot::capture(manager).to_config();
+
+    if !show_diff {
+        println!("# Generated by instant-dev snapshot.");
+        print!("{}", toml::to_string_pretty(&config).unwrap_or_default());
+        return;
+    }
+
+    let Some(team) = manifest::load() else {
+        eprintln!(
+            "No usable {} to compare with.",
+            manifest::config_path().display()
+        );
+        return;
+    };
+
+    let changes = diff(&config, &team);
+    if changes.is_empty() {
+        println!("This machine has nothing the config lacks.");
+        return;
+    }
+
+    println!("This machine has entries the config lacks:");
+    for change in changes {
+        match change.configured {
+            Some(configured) => println!(
+                "  {}: {}{}{} -> {}{}{}",
+                change.key,
+                color::Fg(color::Red),
+                configured,
+                color::Fg(color::Reset),
+                color::Fg(color::Green),
+                change.value,
+                color::Fg(color::Reset)
+            ),
+            None => println!(
+                "  {}: {}+ {}{}",
+                change.key,
+                color::Fg(color::Green),
+                change.value,
+                color::Fg(color::Reset)
+            ),
+        }
+    }
+}
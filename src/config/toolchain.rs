@@ -0,0 +1,141 @@
+use std::io;
+use std::path::PathBuf;
+use termion::color;
+use toml::Value;
+
+use crate::config::{go, node, python, rust};
+use crate::system::shell::Shell;
+use crate::system::{environment, exec};
+
+/// A tool installed by a toolchain, such as a `go install` binary or a global
+/// npm package.
+pub trait Tool {
+    /// Name of the tool, as shown to the user.
+    fn name(&self) -> String;
+
+    /// Check if the tool is already installed at the requested version.
+    fn is_installed(&self) -> bool;
+
+    /// Install the tool.
+    fn install(&self) -> io::Result<()>;
+}
+
+/// A language toolchain set up by instant-dev.
+///
+/// Implementations are registered in [`registered`] and driven by [`configure`],
+/// which detects or installs the toolchain, adds its directories to the managed
+/// shell environment, applies its settings, installs its tools and verifies it.
+pub trait Toolchain {
+    /// Name of the toolchain, as shown in the stage header.
+    fn name(&self) -> &'static str;
+
+    /// Key of the toolchain's section in `packages.toml`, which also names its
+    /// stage in `[hooks]`.
+    fn section(&self) -> &'static str;
+
+    /// Check if the toolchain is already installed.
+    fn detect(&self) -> bool;
+
+    /// Install the toolchain, or explain why it cannot be installed.
+    fn install(&self) -> io::Result<()>;
+
+    /// Apply toolchain settings such as versions, components or environment.
+    fn configure(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// The declared tools to install with the toolchain.
+    fn tools(&self) -> Vec<Box<dyn Tool>>;
+
+    /// Directories to add to `PATH` through the managed shell environment.
+    fn env_paths(&self) -> Vec<PathBuf>;
+
+    /// Check the toolchain works once it is set up.
+    fn verify(&self) -> io::Result<()>;
+}
+
+/// Build the toolchains declared in the config.
+///
+/// Go runs when `[go]` is present or `go_installed` reports Go is already on the
+/// machine, with default tools when `[go]` is absent. Rust, Node.js and Python run
+/// when their section is present.
+pub fn registered(config: Option<&Value>, go_installed: bool) -> Vec<Box<dyn Toolchain>> {
+    let mut toolchains: Vec<Box<dyn Toolchain>> = Vec::new();
+    if go_installed || config.is_some_and(|config| config.get("go").is_some()) {
+        toolchains.push(Box::new(go::GoToolchain::new(config)));
+    }
+
+    let Some(config) = config else {
+        return toolchains;
+    };
+
+    if let Some(rust) = rust::RustConfig::from_config(config) {
+        toolchains.push(Box::new(rust));
+    }
+    match node::NodeConfig::from_config(config) {
+        Ok(Some(node)) => toolchains.push(Box::new(node)),
+        Ok(None) => {}
+        Err(error) => eprintln!("Invalid [node] configuration: {}", error),
+    }
+    match python::PythonConfig::from_config(config) {
+        Ok(Some(python)) => toolchains.push(Box::new(python)),
+        Ok(None) => {}
+        Err(error) => eprintln!("Invalid [python] configuration: {}", error),
+    }
+    toolchains
+}
+
+/// Set up a toolchain.
+///
+/// Installs it if it is missing, adds its directories to the managed shell
+/// environment, applies its settings, installs its tools and verifies it.
+pub fn configure(toolchain: &dyn Toolchain) {
+    println!(
+        "\n{} --- Configuring {} --- {}",
+        color::Fg(color::Yellow),
+        toolchain.name(),
+        color::Fg(color::Reset)
+    );
+
+    if !toolchain.detect() && !exec::install(toolchain.name(), || toolchain.install()) {
+        return;
+    }
+
+    for dir in toolchain.env_paths() {
+        if let Err(e) = environment::add_path(&dir.to_string_lossy()) {
+            eprintln!("Error updating the shell environment: {}", e);
+        }
+    }
+
+    if let Err(e) = toolchain.configure() {
+        eprintln!("Error configuring {}: {}", toolchain.name(), e);
+    }
+
+    for tool in toolchain.tools() {
+        if tool.is_installed() {
+            println!("{} is already installed.", tool.name());
+            continue;
+        }
+
+        exec::install(&tool.name(), || tool.install());
+    }
+
+    match toolchain.verify() {
+        Ok(()) => println!("{} is ready.", toolchain.name()),
+        Err(e) => eprintln!("{} verification failed: {}", toolchain.name(), e),
+    }
+}
+
+/// Print a message to instruct the user to reload their shell configuration.
+pub fn print_reload_hint() {
+    let home = dirs::home_dir().unwrap_or_default();
+    println!("\n-----------------------------------------------");
+    println!("Please run the following command in your shell:");
+    println!(
+        "{}{}{}",
+        color::Fg(color::Green),
+        Shell::detect().reload_hint(&home),
+        color::Fg(color::Reset)
+    );
+    println!("-----------------------------------------------");
+}
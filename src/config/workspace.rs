@@ -0,0 +1,260 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use termion::color;
+use toml::Value;
+
+use crate::config::manifest;
+use crate::system::{exec, path};
+
+/// A repo cloned into the workspace, from a `[[repos]]` entry.
+///
+/// ```toml
+/// [workspace]
+/// root = "~/src"        # where repos without a `path` go
+/// concurrency = 4
+///
+/// [[repos]]
+/// url = "git@github.com:acme/billing.git"
+/// branch = "main"                 # optional
+/// path = "~/src/acme/billing"     # optional, defaults to root/<name>
+/// post_clone = "make setup"       # optional, run once after cloning
+/// ```
+///
+/// A `post_clone` command that fails is run again on the next sync.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repo {
+    pub url: String,
+    pub path: PathBuf,
+    pub branch: Option<String>,
+    pub post_clone: Option<String>,
+}
+
+/// Workspace settings from the `[workspace]` table and `[[repos]]` list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workspace {
+    pub concurrency: usize,
+    pub repos: Vec<Repo>,
+}
+
+/// What the stage did with a repo.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RepoStatus {
+    /// The repo was missing and was cloned, running its post-clone command.
+    Cloned,
+    /// The repo already existed and was fetched.
+    Fetched,
+}
+
+/// Marker in `.git` for a checkout whose `post_clone` command has not succeeded yet.
+const POST_CLONE_PENDING: &str = "instant-dev-post-clone-pending";
+
+/// Name of a repo from its URL, e.g. `billing` for `git@github.com:acme/billing.git`.
+pub fn repo_name(url: &str) -> &str {
+    let url = url.trim_end_matches('/');
+    let name = url.rsplit(['/', ':']).next().unwrap_or(url);
+    name.strip_suffix(".git").unwrap_or(name)
+}
+
+/// Check if `url` clones from GitHub over SSH, which needs the Git stage's GitHub access.
+pub fn is_github_ssh(url: &str) -> bool {
+    url.starts_with("git@github.com:") || url.starts_with("ssh://git@github.com/")
+}
+
+impl Workspace {
+    /// Read the workspace settings, or `None` if no repos are declared.
+    ///
+    /// Invalid repo entries are reported and skipped.
+    pub fn from_config(config: &Value) -> Option<Workspace> {
+        let repos = config.get("repos")?.as_array()?;
+        let workspace = config.get("workspace");
+        let root = workspace
+            .and_then(|workspace| workspace.get("root"))
+            .and_then(Value::as_str)
+            .unwrap_or("~/src");
+        let concurrency = workspace
+            .and_then(|workspace| workspace.get("concurrency"))
+            .and_then(Value::as_integer)
+            .filter(|concurrency| *concurrency > 0)
+            .unwrap_or(4) as usize;
+
+        let field =
+            |repo: &Value, key: &str| repo.get(key).and_then(Value::as_str).map(str::to_string);
+        let repos = repos
+            .iter()
+            .filter_map(|repo| {
+                let Some(url) = field(repo, "url") else {
+                    eprintln!("Invalid repo entry, `url` is required: {}", repo);
+                    return None;
+                };
+                let path = match field(repo, "path") {
+                    Some(path) => path::expand_home(&path),
+                    None => path::expand_home(root).join(repo_name(&url)),
+                };
+                Some(Repo {
+                    path,
+                    branch: field(repo, "branch"),
+                    post_clone: field(repo, "post_clone"),
+                    url,
+                })
+            })
+            .collect();
+
+        Some(Workspace { concurrency, repos })
+    }
+
+    /// Sync every repo, at most `concurrency` at a time.
+    ///
+    /// `progress` is called as each repo finishes; the results keep the
+    /// order of `repos`.
+    pub fn sync<F>(&self, progress: F) -> Vec<Result<RepoStatus, String>>
+    where
+        F: Fn(&Repo, &Result<RepoStatus, String>) + Sync,
+    {
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new(vec![None; self.repos.len()]);
+        let workers = self.concurrency.min(self.repos.len());
+
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(repo) = self.repos.get(index) else {
+                        break;
+                    };
+                    let result = sync_repo(repo);
+                    progress(repo, &result);
+                    results.lock().unwrap()[index] = Some(result);
+                });
+            }
+        });
+
+        results
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .map(|result| result.unwrap_or_else(|| Err("not synced".to_string())))
+            .collect()
+    }
+}
+
+/// Run the `post_clone` command of `repo` in its checkout.
+///
+/// A marker in `.git` stays behind until the command succeeds, so a failed
+/// command is retried on the next sync.
+fn post_clone(repo: &Repo) -> Result<(), String> {
+    let marker = repo.path.join(".git").join(POST_CLONE_PENDING);
+    if let Some(post_clone) = &repo.post_clone {
+        fs::write(&marker, post_clone).map_err(|e| e.to_string())?;
+        exec::run(&mut exec::shell(post_clone, Some(&repo.path)))
+            .map_err(|e| format!("`{}` failed: {}", post_clone, e))?;
+    }
+    if marker.exists() {
+        fs::remove_file(&marker).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Clone `repo` if it is missing, or fetch it if it already exists.
+///
+/// An existing checkout whose `post_clone` command failed runs it again.
+pub fn sync_repo(repo: &Repo) -> Result<RepoStatus, String> {
+    if repo.path.join(".git").exists() {
+        exec::run(
+            Command::new("git")
+                .arg("-C")
+                .arg(&repo.path)
+                .args(["fetch", "--quiet", "--all", "--prune"]),
+        )
+        .map_err(|e| e.to_string())?;
+        if repo.path.join(".git").join(POST_CLONE_PENDING).exists() {
+            post_clone(repo).map_err(|e| format!("fetched, but {}", e))?;
+        }
+        return Ok(RepoStatus::Fetched);
+    }
+    if repo.path.exists()
+        && repo
+            .path
+            .read_dir()
+            .is_ok_and(|mut dir| dir.next().is_some())
+    {
+        return Err(format!(
+            "{} exists and is not a git repo",
+            repo.path.display()
+        ));
+    }
+
+    let mut clone = Command::new("git");
+    clone.args(["clone", "--quiet"]);
+    if let Some(branch) = &repo.branch {
+        clone.args(["--branch", branch]);
+    }
+    exec::run(clone.arg(&repo.url).arg(&repo.path)).map_err(|e| e.to_string())?;
+
+    post_clone(repo).map_err(|e| format!("cloned, but {}", e))?;
+    Ok(RepoStatus::Cloned)
+}
+
+/// Shorten `path` to start with `~` when it is inside `home`.
+fn display_path(path: &Path, home: &Path) -> String {
+    match path.strip_prefix(home) {
+        Ok(relative) => format!("~/{}", relative.display()),
+        Err(_) => path.display().to_string(),
+    }
+}
+
+/// Configure the project workspace.
+///
+/// This function runs after the Git stage when `packages.toml` declares `[[repos]]`.
+/// Missing repos are cloned and their `post_clone` command is run in the checkout;
+/// existing repos are fetched, retrying a `post_clone` command that failed before.
+/// Up to `[workspace] concurrency` repos are synced at once, and each one reports
+/// its status as it finishes. GitHub SSH repos are skipped when `git_authenticated`
+/// reports that the Git stage could not set up GitHub access.
+pub fn configure_workspace(git_authenticated: bool) {
+    let Some(mut workspace) = manifest::load().and_then(|config| Workspace::from_config(&config))
+    else {
+        return;
+    };
+
+    if !git_authenticated {
+        workspace.repos.retain(|repo| {
+            if is_github_ssh(&repo.url) {
+                eprintln!(
+                    "Skipping {}: GitHub access is not configured.",
+                    repo_name(&repo.url)
+                );
+            }
+            !is_github_ssh(&repo.url)
+        });
+        if workspace.repos.is_empty() {
+            return;
+        }
+    }
+
+    println!(
+        "\n{} --- Syncing workspace repos --- {}",
+        color::Fg(color::Yellow),
+        color::Fg(color::Reset)
+    );
+
+    let home = dirs::home_dir().unwrap_or_default();
+    let results = workspace.sync(|repo, result| {
+        let path = display_path(&repo.path, &home);
+        match result {
+            Ok(RepoStatus::Cloned) => println!("{}: cloned into {}", repo_name(&repo.url), path),
+            Ok(RepoStatus::Fetched) => println!("{}: fetched {}", repo_name(&repo.url), path),
+            Err(e) => eprintln!("{}: {}", repo_name(&repo.url), e),
+        }
+    });
+
+    let failed = results.iter().filter(|result| result.is_err()).count();
+    if failed == 0 {
+        println!("All {} repos are up to date.", results.len());
+    } else {
+        eprintln!("{} of {} repos failed to sync.", failed, results.len());
+    }
+}
@@ -0,0 +1,38 @@
+pub mod cli;
+pub mod brew {
+    pub mod brew;
+    pub mod bundle;
+    pub mod packages;
+}
+pub mod config {
+    pub mod custom;
+    pub mod dotfiles;
+    pub mod git;
+    pub mod gitconfig;
+    pub mod gitignore;
+    pub mod go;
+    pub mod hooks;
+    pub mod layers;
+    pub mod macos;
+    pub mod manifest;
+    pub mod node;
+    pub mod profiles;
+    pub mod python;
+    pub mod remote;
+    pub mod rust;
+    pub mod signing;
+    pub mod snapshot;
+    pub mod toolchain;
+    pub mod workspace;
+}
+pub mod system {
+    pub mod clipboard;
+    pub mod environment;
+    pub mod exec;
+    pub mod managed;
+    pub mod os;
+    pub mod package_manager;
+    pub mod path;
+    pub mod settings;
+    pub mod shell;
+}
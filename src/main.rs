@@ -1,16 +1,150 @@
-mod brew {
-    pub mod brew;
-    pub mod packages;
+use std::process::exit;
+
+use instant_dev::cli::{Cli, Command, USAGE};
+use instant_dev::config::hooks::{Hooks, When};
+use instant_dev::system::{environment, exec, package_manager};
+use instant_dev::{brew, config};
+
+fn main() {
+    let cli = match Cli::parse(std::env::args().skip(1)) {
+        Ok(cli) => cli,
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, USAGE);
+            exit(2);
+        }
+    };
+
+    if let Some(location) = &cli.config {
+        match config::remote::config_path(location) {
+            Ok(path) => config::manifest::set_config_path(path),
+            Err(error) => {
+                eprintln!("{}", error);
+                exit(1);
+            }
+        }
+    }
+
+    exec::set_dry_run(cli.dry_run);
+    if let Some(profiles) = &cli.profiles {
+        config::profiles::select(profiles.clone());
+    }
+
+    match &cli.command {
+        Command::Setup => setup(&cli),
+        Command::Uninstall => uninstall(),
+        Command::DotfilesPull => config::dotfiles::pull(),
+        Command::ImportBrewfile(path) => report(brew::bundle::import_brewfile(path)),
+        Command::ExportBrewfile(path) => report(brew::bundle::export_brewfile(path)),
+        Command::Snapshot { diff } => {
+            let config = config::manifest::load();
+            let manager = package_manager::detect(config.as_ref(), false);
+            config::snapshot::snapshot(manager.as_ref(), *diff);
+        }
+        Command::ConfigShow { resolved } => report(config::manifest::show(*resolved)),
+        Command::Profiles => report(config::profiles::list()),
+        Command::UseProfiles(names) => report(config::profiles::save(names)),
+        Command::Help => println!("{}", USAGE),
+    }
+}
+
+/// Print an error from a one-off command and exit with a failure status.
+fn report(result: std::io::Result<()>) {
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        exit(1);
+    }
 }
-mod config {
-    pub mod git;
-    pub mod go;
+
+/// Run every setup stage.
+fn setup(cli: &Cli) {
+    let config = config::manifest::load();
+    let hooks = match config.as_ref().map(Hooks::from_config) {
+        Some(Ok(hooks)) => hooks,
+        Some(Err(error)) => {
+            eprintln!("Invalid [hooks] configuration: {}", error);
+            exit(1);
+        }
+        None => Hooks::default(),
+    };
+
+    let manager = package_manager::detect(config.as_ref(), cli.install_brew);
+    let mut manager_ready = true;
+    stage(&hooks, "brew", true, || {
+        if exec::is_dry_run() {
+            println!("Would prepare {}.", manager.name());
+        } else if let Err(e) = manager.prepare() {
+            eprintln!("Error preparing {}: {}", manager.name(), e);
+            manager_ready = false;
+        }
+    });
+    stage(&hooks, "packages", true, || {
+        if manager_ready {
+            brew::packages::packages(manager.as_ref(), config.as_ref())
+        } else {
+            eprintln!("\nSkipping packages: {} is not ready.", manager.name());
+        }
+    });
+    for toolchain in
+        config::toolchain::registered(config.as_ref(), config::go::check_go_installed())
+    {
+        stage(&hooks, toolchain.section(), false, || {
+            config::toolchain::configure(toolchain.as_ref())
+        });
+    }
+    stage(&hooks, "custom", true, config::custom::configure_custom);
+    if !exec::is_dry_run() {
+        config::toolchain::print_reload_hint();
+    }
+    let mut git_authenticated = false;
+    stage(&hooks, "git", false, || {
+        git_authenticated = config::git::configure_git(cli.verbose)
+    });
+    stage(
+        &hooks,
+        "dotfiles",
+        false,
+        config::dotfiles::configure_dotfiles,
+    );
+    stage(&hooks, "workspace", false, || {
+        config::workspace::configure_workspace(git_authenticated)
+    });
+    stage(&hooks, "macos", false, config::macos::configure_macos);
 }
 
-fn main() {
-    let verbose = false;
-    let _ = brew::brew::install_homebrew();
-    brew::packages::packages();
-    config::go::configure_go();
-    config::git::configure_git(verbose)
+/// Run a setup stage between its hooks, exiting if a hook set to abort fails.
+///
+/// In dry-run mode, stages that cannot report their changes are skipped.
+fn stage(hooks: &Hooks, name: &str, supports_dry_run: bool, run: impl FnOnce()) {
+    let config_path = config::manifest::config_path();
+    let dir = config_path.parent();
+    let run_hooks = |when| {
+        if let Err(e) = hooks.run(name, when, dir) {
+            eprintln!("Aborting setup: {}", e);
+            exit(1);
+        }
+    };
+
+    run_hooks(When::Pre);
+    if exec::is_dry_run() && !supports_dry_run {
+        println!("\nSkipping the {} stage in dry-run mode.", name);
+    } else {
+        run();
+    }
+    run_hooks(When::Post);
+}
+
+/// Remove the managed environment from the user's shell configuration.
+fn uninstall() {
+    match environment::uninstall() {
+        Ok(changed) => {
+            for rc_file in changed {
+                println!("Removed instant-dev from {}.", rc_file.display());
+            }
+            println!("instant-dev shell environment removed.");
+        }
+        Err(error) => {
+            eprintln!("Error removing shell environment: {}", error);
+            exit(1);
+        }
+    }
 }
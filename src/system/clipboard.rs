@@ -0,0 +1,139 @@
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+use crate::system::path;
+
+/// A way of putting text on the system clipboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Clipboard {
+    Pbcopy,
+    WlCopy,
+    Xclip,
+    Xsel,
+    Osc52,
+}
+
+impl Clipboard {
+    /// Name of the backend, as shown to the user.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Clipboard::Pbcopy => "pbcopy",
+            Clipboard::WlCopy => "wl-copy",
+            Clipboard::Xclip => "xclip",
+            Clipboard::Xsel => "xsel",
+            Clipboard::Osc52 => "OSC 52",
+        }
+    }
+
+    /// Program and arguments used to write stdin to the clipboard.
+    fn command(&self) -> Option<(&'static str, &'static [&'static str])> {
+        match self {
+            Clipboard::Pbcopy => Some(("pbcopy", &[])),
+            Clipboard::WlCopy => Some(("wl-copy", &[])),
+            Clipboard::Xclip => Some(("xclip", &["-selection", "clipboard"])),
+            Clipboard::Xsel => Some(("xsel", &["--clipboard", "--input"])),
+            Clipboard::Osc52 => None,
+        }
+    }
+
+    /// Copy `text` using this backend.
+    pub fn copy(&self, text: &str) -> io::Result<()> {
+        let Some((program, args)) = self.command() else {
+            let mut stdout = io::stdout();
+            stdout.write_all(osc52_sequence(text).as_bytes())?;
+            return stdout.flush();
+        };
+
+        // wl-copy, xclip and xsel fork a process that keeps serving the clipboard.
+        // It inherits stdout and stderr, so piping them would block until it exits.
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+
+        let status = child.wait()?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!(
+                "{} exited with {}",
+                program, status
+            )))
+        }
+    }
+}
+
+/// List the usable clipboard backends in order of preference.
+///
+/// `has_binary` reports whether a program is on `PATH`, and `is_tty` whether
+/// stdout is a terminal that can receive OSC 52 escape sequences.
+pub fn candidates(has_binary: impl Fn(&str) -> bool, is_tty: bool) -> Vec<Clipboard> {
+    let mut found: Vec<Clipboard> = [
+        Clipboard::Pbcopy,
+        Clipboard::WlCopy,
+        Clipboard::Xclip,
+        Clipboard::Xsel,
+    ]
+    .into_iter()
+    .filter(|clipboard| {
+        clipboard
+            .command()
+            .is_some_and(|(program, _)| has_binary(program))
+    })
+    .collect();
+
+    if is_tty {
+        found.push(Clipboard::Osc52);
+    }
+    found
+}
+
+/// Copy `text` to the clipboard with the first backend that works.
+///
+/// Returns the backend that succeeded, or `None` if no clipboard is available.
+pub fn copy(text: &str) -> Option<Clipboard> {
+    let is_tty = termion::is_tty(&io::stdout());
+    candidates(path::has_binary, is_tty)
+        .into_iter()
+        .find(|clipboard| clipboard.copy(text).is_ok())
+}
+
+/// Build the OSC 52 escape sequence that asks the terminal to set the clipboard.
+pub fn osc52_sequence(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", base64(text.as_bytes()))
+}
+
+/// Standard base64 encoding with padding.
+fn base64(input: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(input.len().div_ceil(3) * 4);
+    for chunk in input.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+
+        encoded.push(ALPHABET[(n >> 18) as usize & 63] as char);
+        encoded.push(ALPHABET[(n >> 12) as usize & 63] as char);
+        encoded.push(if chunk.len() > 1 {
+            ALPHABET[(n >> 6) as usize & 63] as char
+        } else {
+            '='
+        });
+        encoded.push(if chunk.len() > 2 {
+            ALPHABET[n as usize & 63] as char
+        } else {
+            '='
+        });
+    }
+    encoded
+}
@@ -0,0 +1,321 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use toml::Value;
+
+use crate::config::manifest;
+use crate::system::managed;
+use crate::system::shell::Shell;
+
+/// The line the original Go stage appended to `~/.zshrc`.
+const LEGACY_GOPATH_LINE: &str = "export PATH=${PATH}:`go env GOPATH`/bin";
+
+/// PATH entries and environment variables contributed by the setup stages.
+///
+/// The entries are stored in `~/.config/instant-dev/env.toml` and rendered
+/// into a generated script for the login shell (e.g. `env.sh`), which the
+/// shell's rc file sources through a single marked line. Nushell loads its
+/// script from the vendor autoload directory instead.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Environment {
+    pub paths: Vec<String>,
+    pub vars: BTreeMap<String, String>,
+    /// Commands whose output is evaluated as shell code, e.g. `fnm env`.
+    pub evals: Vec<String>,
+    /// POSIX scripts sourced by zsh and bash, e.g. `nvm.sh`.
+    pub sources: Vec<String>,
+}
+
+impl Environment {
+    /// Read the environment from its TOML representation.
+    pub fn from_value(value: &Value) -> Environment {
+        Environment {
+            paths: manifest::strings(value, &["paths"]),
+            vars: value
+                .get("vars")
+                .and_then(Value::as_table)
+                .map(|vars| {
+                    vars.iter()
+                        .filter_map(|(key, value)| {
+                            value.as_str().map(|value| (key.clone(), value.to_string()))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            evals: manifest::strings(value, &["evals"]),
+            sources: manifest::strings(value, &["sources"]),
+        }
+    }
+
+    /// Convert the environment to its TOML representation.
+    pub fn to_value(&self) -> Value {
+        let mut table = toml::value::Table::new();
+        table.insert(
+            "paths".to_string(),
+            Value::Array(self.paths.iter().cloned().map(Value::String).collect()),
+        );
+        table.insert(
+            "evals".to_string(),
+            Value::Array(self.evals.iter().cloned().map(Value::String).collect()),
+        );
+        table.insert(
+            "sources".to_string(),
+            Value::Array(self.sources.iter().cloned().map(Value::String).collect()),
+        );
+        table.insert(
+            "vars".to_string(),
+            Value::Table(
+                self.vars
+                    .iter()
+                    .map(|(key, value)| (key.clone(), Value::String(value.clone())))
+                    .collect(),
+            ),
+        );
+        Value::Table(table)
+    }
+
+    /// Load the saved environment, or an empty one if nothing was saved yet.
+    pub fn load() -> Environment {
+        fs::read_to_string(state_path())
+            .ok()
+            .and_then(|content| toml::from_str::<Value>(&content).ok())
+            .map(|value| Environment::from_value(&value))
+            .unwrap_or_default()
+    }
+
+    /// Add `dir` to the PATH entries. Returns whether it was new.
+    pub fn add_path(&mut self, dir: &str) -> bool {
+        if self.paths.iter().any(|path| path == dir) {
+            return false;
+        }
+        self.paths.push(dir.to_string());
+        true
+    }
+
+    /// Evaluate the output of `command` at shell startup. Returns whether it was new.
+    pub fn add_eval(&mut self, command: &str) -> bool {
+        if self.evals.iter().any(|eval| eval == command) {
+            return false;
+        }
+        self.evals.push(command.to_string());
+        true
+    }
+
+    /// Source the POSIX script `file` at shell startup. Returns whether it was new.
+    pub fn add_source(&mut self, file: &str) -> bool {
+        if self.sources.iter().any(|source| source == file) {
+            return false;
+        }
+        self.sources.push(file.to_string());
+        true
+    }
+
+    /// Set an environment variable. Returns whether the value changed.
+    pub fn set_var(&mut self, key: &str, value: &str) -> bool {
+        self.vars
+            .insert(key.to_string(), value.to_string())
+            .as_deref()
+            != Some(value)
+    }
+
+    /// Render the generated script for `shell`.
+    pub fn render(&self, shell: Shell) -> String {
+        let mut script =
+            String::from("# Generated by instant-dev. Do not edit; changes are overwritten.\n");
+        for (key, value) in &self.vars {
+            script.push_str(&shell.env_line(key, value));
+            script.push('\n');
+        }
+        for path in &self.paths {
+            script.push_str(&shell.path_line(path));
+            script.push('\n');
+        }
+        for eval in &self.evals {
+            match shell.eval_line(eval) {
+                Some(line) => script.push_str(&line),
+                None => script.push_str(&format!(
+                    "# `{}` is not supported by {}",
+                    eval,
+                    shell.name()
+                )),
+            }
+            script.push('\n');
+        }
+        for source in &self.sources {
+            match shell.source_script_line(source) {
+                Some(line) => script.push_str(&line),
+                None => script.push_str(&format!(
+                    "# {} is not supported by {}",
+                    source,
+                    shell.name()
+                )),
+            }
+            script.push('\n');
+        }
+        script
+    }
+
+    /// Lines that earlier versions appended to the rc file of `shell` and
+    /// that the generated script now covers.
+    pub fn legacy_lines(&self, shell: Shell) -> Vec<String> {
+        let mut lines = vec![LEGACY_GOPATH_LINE.to_string()];
+        lines.extend(self.paths.iter().map(|path| shell.path_line(path)));
+        lines
+    }
+
+    /// Save the environment, regenerate the script for `shell`, and make sure
+    /// the shell's rc file sources it.
+    pub fn save(&self, shell: Shell) -> io::Result<()> {
+        fs::create_dir_all(manifest::config_dir())?;
+        fs::write(
+            state_path(),
+            toml::to_string(&self.to_value()).unwrap_or_default(),
+        )?;
+        let script = script_path(shell);
+        if let Some(parent) = script.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(script, self.render(shell))?;
+        install(shell, &self.legacy_lines(shell))?;
+        Ok(())
+    }
+}
+
+/// Where the environment entries are stored.
+fn state_path() -> PathBuf {
+    manifest::config_dir().join("env.toml")
+}
+
+/// The generated script for `shell`.
+///
+/// Nushell resolves `source` while parsing, so a missing script would stop
+/// it from starting. Its script goes in the vendor autoload directory, which
+/// Nushell reads on its own when the file exists.
+pub fn script_path(shell: Shell) -> PathBuf {
+    let name = match shell {
+        Shell::Zsh | Shell::Bash => "env.sh",
+        Shell::Fish => "env.fish",
+        Shell::Nushell => {
+            return dirs::data_dir()
+                .unwrap_or_else(manifest::config_dir)
+                .join("nushell")
+                .join("vendor")
+                .join("autoload")
+                .join("instant-dev.nu")
+        }
+    };
+    manifest::config_dir().join(name)
+}
+
+/// The line that sources `script` from the rc file of `shell`.
+///
+/// Nushell autoloads its script, so it gets `None`.
+pub fn source_line(shell: Shell, script: &Path) -> Option<String> {
+    let script = script.display();
+    match shell {
+        Shell::Zsh | Shell::Bash => Some(format!("[ -f \"{0}\" ] && . \"{0}\"", script)),
+        Shell::Fish => Some(format!("test -f '{0}'; and source '{0}'", script)),
+        Shell::Nushell => None,
+    }
+}
+
+/// Rewrite the rc file `content` of `shell` so it sources `script` from the
+/// marked block, dropping any `legacy` lines left by earlier versions.
+pub fn rc_content(shell: Shell, content: &str, script: &Path, legacy: &[String]) -> String {
+    let kept: Vec<&str> = content
+        .lines()
+        .filter(|line| !legacy.iter().any(|legacy| legacy.trim() == line.trim()))
+        .collect();
+    let kept = if kept.is_empty() {
+        String::new()
+    } else {
+        format!("{}\n", kept.join("\n"))
+    };
+    let lines: Vec<String> = source_line(shell, script).into_iter().collect();
+    managed::replace_block(&kept, &lines)
+}
+
+/// Add the marked source line to the rc file of `shell` and remove the
+/// `legacy` lines it replaces.
+///
+/// Returns whether the rc file changed.
+pub fn install(shell: Shell, legacy: &[String]) -> io::Result<bool> {
+    let home = dirs::home_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Home directory not found"))?;
+    let rc_file = shell.rc_file(&home);
+
+    let existing = fs::read_to_string(&rc_file).unwrap_or_default();
+    let updated = rc_content(shell, &existing, &script_path(shell), legacy);
+    if updated == existing {
+        return Ok(false);
+    }
+
+    if let Some(parent) = rc_file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&rc_file, updated)?;
+    Ok(true)
+}
+
+/// Remove the source line from every shell's rc file and delete the
+/// generated scripts.
+///
+/// Returns the rc files that were changed.
+pub fn uninstall() -> io::Result<Vec<PathBuf>> {
+    let home = dirs::home_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Home directory not found"))?;
+    let mut changed = Vec::new();
+
+    for shell in [Shell::Zsh, Shell::Bash, Shell::Fish, Shell::Nushell] {
+        let rc_file = shell.rc_file(&home);
+        let existing = fs::read_to_string(&rc_file).unwrap_or_default();
+        if existing.contains(managed::BEGIN_MARKER) {
+            let stripped = managed::strip_block(&existing);
+            if stripped.is_empty() && shell == Shell::Fish {
+                fs::remove_file(&rc_file)?;
+            } else {
+                fs::write(&rc_file, stripped)?;
+            }
+            changed.push(rc_file);
+        }
+
+        let script = script_path(shell);
+        if script.exists() {
+            fs::remove_file(script)?;
+        }
+    }
+
+    if state_path().exists() {
+        fs::remove_file(state_path())?;
+    }
+    Ok(changed)
+}
+
+/// Add `dir` to the managed PATH for the user's login shell.
+pub fn add_path(dir: &str) -> io::Result<()> {
+    let mut environment = Environment::load();
+    environment.add_path(dir);
+    environment.save(Shell::detect())
+}
+
+/// Set `key` in the managed environment for the user's login shell.
+pub fn set_var(key: &str, value: &str) -> io::Result<()> {
+    let mut environment = Environment::load();
+    environment.set_var(key, value);
+    environment.save(Shell::detect())
+}
+
+/// Evaluate the output of `command` in the managed environment for the user's login shell.
+pub fn add_eval(command: &str) -> io::Result<()> {
+    let mut environment = Environment::load();
+    environment.add_eval(command);
+    environment.save(Shell::detect())
+}
+
+/// Source the POSIX script `file` in the managed environment for the user's login shell.
+pub fn add_source(file: &str) -> io::Result<()> {
+    let mut environment = Environment::load();
+    environment.add_source(file);
+    environment.save(Shell::detect())
+}
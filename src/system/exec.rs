@@ -0,0 +1,77 @@
+use std::io;
+use std::path::Path;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether `--dry-run` was given.
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+
+/// Turn dry-run mode on or off for the rest of the run.
+pub fn set_dry_run(dry_run: bool) {
+    DRY_RUN.store(dry_run, Ordering::Relaxed);
+}
+
+/// Check if changes should only be reported, not made.
+pub fn is_dry_run() -> bool {
+    DRY_RUN.load(Ordering::Relaxed)
+}
+
+/// Build a `sh -c <script>` command, run from `dir` when given.
+pub fn shell(script: &str, dir: Option<&Path>) -> Command {
+    let mut command = Command::new("sh");
+    command.args(["-c", script]);
+    if let Some(dir) = dir {
+        command.current_dir(dir);
+    }
+    command
+}
+
+/// Run `command` and turn a failed exit status into an error.
+///
+/// Output is captured unless the caller set up the command's stdio. The error
+/// holds the last line of captured stderr, or the exit status when there is none.
+pub fn run(command: &mut Command) -> io::Result<()> {
+    let output = command.output()?;
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(io::Error::other(
+        match stderr.lines().rfind(|line| !line.trim().is_empty()) {
+            Some(line) => line.trim().to_string(),
+            None => format!("command exited with {}", output.status),
+        },
+    ))
+}
+
+/// Check if `command` exits successfully, discarding its output.
+pub fn succeeds(command: &mut Command) -> bool {
+    command
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Install `name` with `install`, reporting the result.
+///
+/// In dry-run mode, only reports that `name` would be installed. Returns whether
+/// the install succeeded.
+pub fn install(name: &str, install: impl FnOnce() -> io::Result<()>) -> bool {
+    if is_dry_run() {
+        println!("Would install {}.", name);
+        return true;
+    }
+
+    println!("Installing {}...", name);
+    match install() {
+        Ok(()) => {
+            println!("{} installed successfully.", name);
+            true
+        }
+        Err(e) => {
+            eprintln!("Failed to install {}: {}", name, e);
+            false
+        }
+    }
+}
@@ -0,0 +1,52 @@
+/// First line of a block of lines owned by instant-dev.
+pub const BEGIN_MARKER: &str = "# >>> instant-dev >>>";
+/// Last line of a block of lines owned by instant-dev.
+pub const END_MARKER: &str = "# <<< instant-dev <<<";
+
+/// Remove the instant-dev block from `content`, keeping every other line.
+pub fn strip_block(content: &str) -> String {
+    let mut kept = Vec::new();
+    let mut inside = false;
+
+    for line in content.lines() {
+        match line.trim() {
+            BEGIN_MARKER => inside = true,
+            END_MARKER => inside = false,
+            _ if !inside => kept.push(line),
+            _ => {}
+        }
+    }
+
+    while kept.last().is_some_and(|line| line.trim().is_empty()) {
+        kept.pop();
+    }
+
+    if kept.is_empty() {
+        String::new()
+    } else {
+        format!("{}\n", kept.join("\n"))
+    }
+}
+
+/// Replace the instant-dev block in `content` with `lines`.
+///
+/// The block is appended after any user lines. An empty `lines` removes it.
+pub fn replace_block(content: &str, lines: &[String]) -> String {
+    let mut updated = strip_block(content);
+    if lines.is_empty() {
+        return updated;
+    }
+
+    if !updated.is_empty() {
+        updated.push('\n');
+    }
+    updated.push_str(BEGIN_MARKER);
+    updated.push('\n');
+    for line in lines {
+        updated.push_str(line);
+        updated.push('\n');
+    }
+    updated.push_str(END_MARKER);
+    updated.push('\n');
+    updated
+}
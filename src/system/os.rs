@@ -0,0 +1,32 @@
+/// The operating system instant-dev is running on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Os {
+    MacOs,
+    Linux,
+    Other,
+}
+
+impl Os {
+    /// The operating system this binary was built for.
+    pub fn current() -> Os {
+        Os::from_name(std::env::consts::OS)
+    }
+
+    /// Map a `std::env::consts::OS` value to an [`Os`].
+    pub fn from_name(name: &str) -> Os {
+        match name {
+            "macos" => Os::MacOs,
+            "linux" => Os::Linux,
+            _ => Os::Other,
+        }
+    }
+
+    /// Name of the operating system, as shown to the user.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Os::MacOs => "macOS",
+            Os::Linux => "Linux",
+            Os::Other => std::env::consts::OS,
+        }
+    }
+}
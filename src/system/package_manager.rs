@@ -0,0 +1,326 @@
+use std::io;
+use std::process::Command;
+use toml::Value;
+
+use crate::brew::brew;
+use crate::system::os::Os;
+use crate::system::{exec, path};
+
+/// A system package manager used to install the `packages` list.
+pub trait PackageManager {
+    /// Name of the manager, also used as the key in `[package_map]` entries.
+    fn name(&self) -> &'static str;
+
+    /// Make the manager ready to install packages, e.g. refresh its package index.
+    fn prepare(&self) -> io::Result<()>;
+
+    /// List the installed packages.
+    fn installed(&self) -> Vec<String>;
+
+    /// Install a package.
+    fn install(&self, package: &str) -> io::Result<()>;
+
+    /// List the packages installed on request rather than as dependencies.
+    fn leaves(&self) -> Vec<String> {
+        self.installed()
+    }
+
+    /// List the installed casks, for managers that support them.
+    fn installed_casks(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Install a cask, for managers that support them.
+    fn install_cask(&self, cask: &str) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{} cannot install cask {}", self.name(), cask),
+        ))
+    }
+
+    /// List the added taps, for managers that support them.
+    fn installed_taps(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Add a tap, for managers that support them.
+    fn tap(&self, tap: &str) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{} cannot add tap {}", self.name(), tap),
+        ))
+    }
+
+    /// Check if the manager installs casks and taps.
+    fn supports_casks(&self) -> bool {
+        false
+    }
+}
+
+/// Homebrew, on macOS or Linux.
+///
+/// ```toml
+/// [homebrew]
+/// installer_url = "https://raw.githubusercontent.com/Homebrew/install/<commit>/install.sh"
+/// installer_sha256 = "..."
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Homebrew {
+    /// Run the official installer when Homebrew is missing (`--install-brew`).
+    pub install: bool,
+    pub installer_url: Option<String>,
+    pub installer_sha256: Option<String>,
+}
+
+impl Homebrew {
+    /// Read the `[homebrew]` installer settings from the config.
+    pub fn from_config(config: Option<&Value>, install: bool) -> Homebrew {
+        let setting = |key: &str| {
+            config
+                .and_then(|config| config.get("homebrew"))
+                .and_then(|homebrew| homebrew.get(key))
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        };
+        Homebrew {
+            install,
+            installer_url: setting("installer_url"),
+            installer_sha256: setting("installer_sha256"),
+        }
+    }
+}
+
+/// apt, on Debian and Ubuntu.
+pub struct Apt;
+
+/// dnf, on Fedora and RHEL.
+pub struct Dnf;
+
+/// pacman, on Arch Linux.
+pub struct Pacman;
+
+/// Run `command` and return its stdout lines, or nothing if it fails.
+fn lines(command: &mut Command) -> Vec<String> {
+    match command.output() {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Build a command for `program`, through `sudo` unless already running as root.
+fn privileged(program: &str) -> Command {
+    let is_root = lines(Command::new("id").arg("-u"))
+        .first()
+        .map(String::as_str)
+        == Some("0");
+    if is_root {
+        Command::new(program)
+    } else {
+        let mut command = Command::new("sudo");
+        command.arg(program);
+        command
+    }
+}
+
+/// Parse `dpkg-query -W -f='${db:Status-Abbrev} ${Package}\n'` output into the
+/// names of installed packages.
+pub fn dpkg_installed(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.strip_prefix("ii "))
+        .map(|package| package.trim().to_string())
+        .collect()
+}
+
+impl PackageManager for Homebrew {
+    fn name(&self) -> &'static str {
+        "brew"
+    }
+
+    fn prepare(&self) -> io::Result<()> {
+        brew::install_homebrew(
+            self.install,
+            self.installer_url.as_deref().unwrap_or(brew::INSTALLER_URL),
+            self.installer_sha256.as_deref(),
+        )
+    }
+
+    fn installed(&self) -> Vec<String> {
+        lines(Command::new("brew").args(["list", "--formula", "-1"]))
+    }
+
+    fn install(&self, package: &str) -> io::Result<()> {
+        exec::run(Command::new("brew").args(["install", package]))
+    }
+
+    fn leaves(&self) -> Vec<String> {
+        lines(Command::new("brew").args(["leaves", "--installed-on-request"]))
+    }
+
+    fn installed_casks(&self) -> Vec<String> {
+        lines(Command::new("brew").args(["list", "--cask", "-1"]))
+    }
+
+    fn install_cask(&self, cask: &str) -> io::Result<()> {
+        exec::run(Command::new("brew").args(["install", "--cask", cask]))
+    }
+
+    fn installed_taps(&self) -> Vec<String> {
+        lines(Command::new("brew").arg("tap"))
+    }
+
+    fn tap(&self, tap: &str) -> io::Result<()> {
+        exec::run(Command::new("brew").args(["tap", tap]))
+    }
+
+    fn supports_casks(&self) -> bool {
+        true
+    }
+}
+
+impl PackageManager for Apt {
+    fn name(&self) -> &'static str {
+        "apt"
+    }
+
+    fn prepare(&self) -> io::Result<()> {
+        exec::run(privileged("apt-get").args(["update", "-qq"]))
+    }
+
+    fn installed(&self) -> Vec<String> {
+        let output = Command::new("dpkg-query")
+            .args(["-W", "-f=${db:Status-Abbrev} ${Package}\\n"])
+            .output();
+        output
+            .map(|output| dpkg_installed(&String::from_utf8_lossy(&output.stdout)))
+            .unwrap_or_default()
+    }
+
+    fn install(&self, package: &str) -> io::Result<()> {
+        exec::run(
+            privileged("apt-get")
+                .env("DEBIAN_FRONTEND", "noninteractive")
+                .args(["install", "-y", "-qq", package]),
+        )
+    }
+
+    fn leaves(&self) -> Vec<String> {
+        lines(Command::new("apt-mark").arg("showmanual"))
+    }
+}
+
+impl PackageManager for Dnf {
+    fn name(&self) -> &'static str {
+        "dnf"
+    }
+
+    fn prepare(&self) -> io::Result<()> {
+        exec::run(privileged("dnf").args(["makecache", "-q"]))
+    }
+
+    fn installed(&self) -> Vec<String> {
+        lines(Command::new("rpm").args(["-qa", "--qf", "%{NAME}\\n"]))
+    }
+
+    fn install(&self, package: &str) -> io::Result<()> {
+        exec::run(privileged("dnf").args(["install", "-y", "-q", package]))
+    }
+
+    fn leaves(&self) -> Vec<String> {
+        lines(Command::new("dnf").args(["repoquery", "--userinstalled", "--qf", "%{name}"]))
+    }
+}
+
+impl PackageManager for Pacman {
+    fn name(&self) -> &'static str {
+        "pacman"
+    }
+
+    fn prepare(&self) -> io::Result<()> {
+        exec::run(privileged("pacman").args(["-Sy", "--noconfirm"]))
+    }
+
+    fn installed(&self) -> Vec<String> {
+        lines(Command::new("pacman").arg("-Qq"))
+    }
+
+    fn install(&self, package: &str) -> io::Result<()> {
+        exec::run(privileged("pacman").args(["-S", "--noconfirm", "--needed", package]))
+    }
+
+    fn leaves(&self) -> Vec<String> {
+        lines(Command::new("pacman").arg("-Qqe"))
+    }
+}
+
+/// Look up a package manager by its name.
+pub fn by_name(name: &str) -> Option<Box<dyn PackageManager>> {
+    match name {
+        "brew" | "homebrew" => Some(Box::new(Homebrew::default())),
+        "apt" => Some(Box::new(Apt)),
+        "dnf" => Some(Box::new(Dnf)),
+        "pacman" => Some(Box::new(Pacman)),
+        _ => None,
+    }
+}
+
+/// Pick the package manager for this machine.
+///
+/// `package_manager = "apt"` in the config wins. Otherwise macOS uses Homebrew,
+/// and Linux uses the first native manager found, falling back to Homebrew.
+/// `install_brew` lets Homebrew install itself when it is missing.
+pub fn detect(config: Option<&Value>, install_brew: bool) -> Box<dyn PackageManager> {
+    let homebrew = || Box::new(Homebrew::from_config(config, install_brew));
+    let configured = config
+        .and_then(|config| config.get("package_manager"))
+        .and_then(Value::as_str);
+    if let Some(name) = configured {
+        match by_name(name) {
+            Some(manager) if manager.name() == "brew" => return homebrew(),
+            Some(manager) => return manager,
+            None => eprintln!("Unknown package manager '{}', detecting one instead.", name),
+        }
+    }
+
+    if Os::current() != Os::Linux {
+        return homebrew();
+    }
+    if path::has_binary("apt-get") {
+        Box::new(Apt)
+    } else if path::has_binary("dnf") {
+        Box::new(Dnf)
+    } else if path::has_binary("pacman") {
+        Box::new(Pacman)
+    } else {
+        homebrew()
+    }
+}
+
+/// The package to install for `tool` with the manager named `manager`.
+///
+/// `[package_map.<tool>]` maps a logical tool name to each manager's package
+/// name; `false` skips the tool for that manager. Unmapped tools keep their name.
+///
+/// ```toml
+/// [package_map.go]
+/// apt = "golang-go"
+/// dnf = "golang"
+///
+/// [package_map.lazygit]
+/// apt = false
+/// ```
+pub fn resolve_package(config: &Value, tool: &str, manager: &str) -> Option<String> {
+    match config
+        .get("package_map")
+        .and_then(|map| map.get(tool))
+        .and_then(|entry| entry.get(manager))
+    {
+        Some(Value::String(package)) => Some(package.clone()),
+        Some(Value::Boolean(false)) => None,
+        _ => Some(tool.to_string()),
+    }
+}
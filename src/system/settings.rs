@@ -0,0 +1,59 @@
+use std::collections::BTreeMap;
+use termion::color;
+
+/// A setting whose current value differs from the desired one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettingChange {
+    pub key: String,
+    pub current: Option<String>,
+    pub desired: String,
+}
+
+/// Compare desired settings against the values returned by `current`.
+pub fn diff_settings(
+    desired: &BTreeMap<String, String>,
+    current: impl Fn(&str) -> Option<String>,
+) -> Vec<SettingChange> {
+    desired
+        .iter()
+        .filter_map(|(key, value)| {
+            let existing = current(key);
+            if existing.as_deref() == Some(value.as_str()) {
+                None
+            } else {
+                Some(SettingChange {
+                    key: key.clone(),
+                    current: existing,
+                    desired: value.clone(),
+                })
+            }
+        })
+        .collect()
+}
+
+/// Print a diff of the settings that will change.
+pub fn print_diff(changes: &[SettingChange]) {
+    for change in changes {
+        match &change.current {
+            Some(current) => println!(
+                "  {}: {}{}{} -> {}{}{}",
+                change.key,
+                color::Fg(color::Red),
+                current,
+                color::Fg(color::Reset),
+                color::Fg(color::Green),
+                change.desired,
+                color::Fg(color::Reset)
+            ),
+            None => println!(
+                "  {}: {}(unset){} -> {}{}{}",
+                change.key,
+                color::Fg(color::Red),
+                color::Fg(color::Reset),
+                color::Fg(color::Green),
+                change.desired,
+                color::Fg(color::Reset)
+            ),
+        }
+    }
+}
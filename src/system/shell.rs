@@ -0,0 +1,198 @@
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A login shell whose startup files instant-dev knows how to edit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Zsh,
+    Bash,
+    Fish,
+    Nushell,
+}
+
+impl Shell {
+    /// Identify a shell from its executable path, e.g. `/bin/zsh`.
+    pub fn from_path(path: &str) -> Option<Shell> {
+        let name = Path::new(path.trim()).file_name()?.to_str()?;
+        match name {
+            "zsh" => Some(Shell::Zsh),
+            "bash" => Some(Shell::Bash),
+            "fish" => Some(Shell::Fish),
+            "nu" | "nushell" => Some(Shell::Nushell),
+            _ => None,
+        }
+    }
+
+    /// Detect the user's login shell.
+    ///
+    /// Looks at `$SHELL` first, then the user's passwd entry. Falls back to
+    /// zsh on macOS and bash elsewhere.
+    pub fn detect() -> Shell {
+        env::var("SHELL")
+            .ok()
+            .and_then(|shell| Shell::from_path(&shell))
+            .or_else(|| passwd_shell().and_then(|shell| Shell::from_path(&shell)))
+            .unwrap_or(if cfg!(target_os = "macos") {
+                Shell::Zsh
+            } else {
+                Shell::Bash
+            })
+    }
+
+    /// Name of the shell, as shown to the user.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Shell::Zsh => "zsh",
+            Shell::Bash => "bash",
+            Shell::Fish => "fish",
+            Shell::Nushell => "nushell",
+        }
+    }
+
+    /// The startup file instant-dev edits for this shell.
+    pub fn rc_file(&self, home: &Path) -> PathBuf {
+        match self {
+            Shell::Zsh => home.join(".zshrc"),
+            Shell::Bash if cfg!(target_os = "macos") => home.join(".bash_profile"),
+            Shell::Bash => home.join(".bashrc"),
+            Shell::Fish => home
+                .join(".config")
+                .join("fish")
+                .join("conf.d")
+                .join("instant-dev.fish"),
+            Shell::Nushell if cfg!(target_os = "macos") => home
+                .join("Library")
+                .join("Application Support")
+                .join("nushell")
+                .join("env.nu"),
+            Shell::Nushell => home.join(".config").join("nushell").join("env.nu"),
+        }
+    }
+
+    /// A line appending `dir` to `PATH`.
+    pub fn path_line(&self, dir: &str) -> String {
+        match self {
+            Shell::Zsh | Shell::Bash => format!("export PATH=\"$PATH:{}\"", dir),
+            Shell::Fish => format!("fish_add_path --append {}", quote(dir)),
+            Shell::Nushell => format!(
+                "$env.PATH = ($env.PATH | split row (char esep) | append {})",
+                double_quote(dir)
+            ),
+        }
+    }
+
+    /// A line exporting the environment variable `key`.
+    pub fn env_line(&self, key: &str, value: &str) -> String {
+        match self {
+            Shell::Zsh | Shell::Bash => format!("export {}={}", key, double_quote(value)),
+            Shell::Fish => format!("set -gx {} {}", key, double_quote(value)),
+            Shell::Nushell => format!("$env.{} = {}", key, double_quote(value)),
+        }
+    }
+
+    /// A line evaluating the shell code printed by `command`, e.g. `fnm env`.
+    ///
+    /// Nushell cannot evaluate generated code at startup, so it gets `None`.
+    pub fn eval_line(&self, command: &str) -> Option<String> {
+        match self {
+            Shell::Zsh | Shell::Bash => Some(format!("eval \"$({})\"", command)),
+            Shell::Fish => Some(format!("{} | source", command)),
+            Shell::Nushell => None,
+        }
+    }
+
+    /// A line sourcing the POSIX shell script `file` if it exists.
+    ///
+    /// Only zsh and bash can source POSIX scripts, so other shells get `None`.
+    pub fn source_script_line(&self, file: &str) -> Option<String> {
+        match self {
+            Shell::Zsh | Shell::Bash => Some(format!("[ -s \"{0}\" ] && . \"{0}\"", file)),
+            Shell::Fish | Shell::Nushell => None,
+        }
+    }
+
+    /// The command the user should run to reload their shell configuration.
+    pub fn reload_hint(&self, home: &Path) -> String {
+        match self {
+            Shell::Zsh | Shell::Bash => format!("source {}", self.rc_file(home).display()),
+            Shell::Fish => "exec fish".to_string(),
+            Shell::Nushell => "exec nu".to_string(),
+        }
+    }
+}
+
+/// Read the login shell from a passwd-formatted `entries` for `user`.
+pub fn shell_from_passwd(entries: &str, user: &str) -> Option<String> {
+    entries.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split(':').collect();
+        if fields.len() == 7 && fields[0] == user {
+            Some(fields[6].to_string())
+        } else {
+            None
+        }
+    })
+}
+
+/// Look up the current user's login shell from the system user database.
+fn passwd_shell() -> Option<String> {
+    let user = env::var("USER").ok()?;
+
+    if cfg!(target_os = "macos") {
+        let output = Command::new("dscl")
+            .args([".", "-read", &format!("/Users/{}", user), "UserShell"])
+            .output()
+            .ok()?;
+        return String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .strip_prefix("UserShell:")
+            .map(|shell| shell.trim().to_string());
+    }
+
+    let entries = Command::new("getent")
+        .args(["passwd", &user])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+        .or_else(|| fs::read_to_string("/etc/passwd").ok())?;
+    shell_from_passwd(&entries, &user)
+}
+
+/// Single-quote a word for fish, which allows `\'` inside single quotes.
+///
+/// Nushell single-quoted strings have no escapes, so it uses [`double_quote`].
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "\\'"))
+}
+
+/// Double-quote a value so variables in it are still expanded.
+fn double_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Append `line` to `file` unless it is already present.
+///
+/// Creates the file and its parent directories when needed. Returns whether
+/// the line was added.
+pub fn ensure_line(file: &Path, line: &str) -> io::Result<bool> {
+    let existing = fs::read_to_string(file).unwrap_or_default();
+    if existing
+        .lines()
+        .any(|existing| existing.trim() == line.trim())
+    {
+        return Ok(false);
+    }
+
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut handle = OpenOptions::new().create(true).append(true).open(file)?;
+    if !existing.is_empty() && !existing.ends_with('\n') {
+        writeln!(handle)?;
+    }
+    writeln!(handle, "{}", line)?;
+    Ok(true)
+}
@@ -25,21 +25,9 @@ mod command_mock {
     }
 }
 
-// Implement the trait for Command so you can easily swap the real implementation with the mock
-impl CommandRunner for std::process::Command {
-    fn output(&self) -> std::io::Result<std::process::Output> {
-        self.output()
-    }
-
-    fn status(&self) -> std::io::Result<std::process::ExitStatus> {
-        self.status()
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
-    use command_mock;
 
     // Add function is_brew_installed_with_command
     fn is_brew_installed_with_command(command_runner: &mut dyn CommandRunner) -> Option<String> {
@@ -75,57 +63,4 @@ mod tests {
         let result = is_brew_installed_with_command(&mut cmd_mock);
         assert_eq!(result, Some("Homebrew 2.7.0".to_string()));
     }
-
-    #[test]
-    fn test_update_homebrew_success() {
-        let mut cmd_mock = command_mock::MockCommandMock::new();
-        cmd_mock.expect_output().times(1).returning(|| {
-            Ok(std::process::Output {
-                status: ExitStatusExt::from_raw(0),
-                stdout: "Homebrew updated.".to_string().into_bytes(),
-                stderr: Vec::new(),
-            })
-        });
-
-        let result = update_homebrew();
-        assert!(result.is_ok());
-    }
-
-    #[test]
-    fn test_update_homebrew_failure() {
-        let mut cmd_mock = command_mock::MockCommandMock::new();
-        cmd_mock.expect_output().times(1).returning(|| {
-            Ok(std::process::Output {
-                status: ExitStatusExt::from_raw(1), // Indicating failure
-                stdout: "".to_string().into_bytes(),
-                stderr: Vec::new(),
-            })
-        });
-
-        let result = update_homebrew();
-        assert!(result.is_err());
-    }
-
-    #[test]
-    fn test_install_homebrew_success() {
-        let mut cmd_mock = command_mock::MockCommandMock::new();
-        cmd_mock
-            .expect_status()
-            .times(1)
-            .returning(|| Ok(std::process::ExitStatus::from_raw(0)));
-
-        let result = install_homebrew_from_script();
-        assert!(result.is_ok());
-    }
-
-    #[test]
-    fn test_install_homebrew_failure() {
-        let mut cmd_mock = command_mock::MockCommandMock::new();
-        cmd_mock.expect_status().times(1).returning(|| {
-            Ok(std::process::ExitStatus::from_raw(1)) // Indicating failure
-        });
-
-        let result = install_homebrew_from_script();
-        assert!(result.is_err());
-    }
 }
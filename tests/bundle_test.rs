@@ -0,0 +1,99 @@
+use std::fs;
+use std::io;
+
+use instant_dev::brew::bundle::{
+    bundle_from_config, import_brewfile, merge_array, merge_bundle, parse_brewfile, render_brewfile,
+};
+use instant_dev::config::{manifest, remote};
+
+mod common;
+
+const BREWFILE: &str = r#"
+# Work laptop
+tap "homebrew/cask-fonts"
+tap "acme/tools", "git@github.com:acme/homebrew-tools.git"
+brew "jq"
+brew "postgresql@16", restart_service: true
+cask "firefox"
+mas "Xcode", id: 497799835
+vscode "rust-lang.rust-analyzer"
+"#;
+
+#[test]
+fn test_parse_brewfile() {
+    let bundle = parse_brewfile(BREWFILE);
+    assert_eq!(bundle.taps, vec!["homebrew/cask-fonts", "acme/tools"]);
+    assert_eq!(bundle.brews, vec!["jq", "postgresql@16"]);
+    assert_eq!(bundle.casks, vec!["firefox"]);
+    assert_eq!(bundle.mas.get("Xcode"), Some(&497799835));
+    assert_eq!(bundle.skipped, vec!["vscode \"rust-lang.rust-analyzer\""]);
+}
+
+#[test]
+fn test_merge_array_keeps_comments_and_order() {
+    let content = "packages = [\"go\", \"jq\"]\n\n# Go tools\n[go]\ntools = []\n";
+    let merged = merge_array(content, "packages", &["jq".to_string(), "fd".to_string()]);
+    assert_eq!(
+        merged,
+        "packages = [\"go\", \"jq\", \"fd\"]\n\n# Go tools\n[go]\ntools = []\n"
+    );
+
+    let merged = merge_array(content, "taps", &["acme/tools".to_string()]);
+    assert_eq!(
+        merged,
+        "packages = [\"go\", \"jq\"]\n\ntaps = [\"acme/tools\"]\n\n# Go tools\n[go]\ntools = []\n"
+    );
+}
+
+#[test]
+fn test_merge_array_stops_at_the_closing_bracket() {
+    let content =
+        "packages = [\"go\", \"jq\"] # core [tools]\n[package_map.go]\napt = \"golang\"\n";
+    let merged = merge_array(content, "packages", &["fd".to_string()]);
+    assert_eq!(
+        merged,
+        "packages = [\"go\", \"jq\", \"fd\"] # core [tools]\n[package_map.go]\napt = \"golang\"\n"
+    );
+
+    let content =
+        "packages = [\n  \"go\", # the ] here is a comment\n  \"a]b\",\n]\n[package_map.go]\n";
+    let merged = merge_array(content, "packages", &["fd".to_string()]);
+    assert_eq!(
+        merged,
+        "packages = [\"go\", \"a]b\", \"fd\"]\n[package_map.go]\n"
+    );
+}
+
+#[test]
+fn test_import_then_export_round_trips() {
+    let bundle = parse_brewfile(BREWFILE);
+    let content = merge_bundle("packages = [\"go\"]\n", &bundle);
+    assert_eq!(merge_bundle(&content, &bundle), content);
+
+    let config: toml::Value = toml::from_str(&content).unwrap();
+    let exported = bundle_from_config(&config);
+    assert_eq!(exported.brews, vec!["go", "jq", "postgresql@16"]);
+    assert_eq!(exported.taps, bundle.taps);
+    assert_eq!(exported.mas, bundle.mas);
+
+    let brewfile = render_brewfile(&exported);
+    assert!(brewfile.contains("tap \"acme/tools\"\n"));
+    assert!(brewfile.contains("cask \"firefox\"\n"));
+    assert!(brewfile.contains("mas \"Xcode\", id: 497799835\n"));
+    assert_eq!(parse_brewfile(&brewfile).brews, exported.brews);
+}
+
+#[test]
+fn test_import_refuses_a_remote_config() {
+    let dir = common::scratch("bundle-remote");
+    let brewfile = dir.join("Brewfile");
+    fs::write(&brewfile, BREWFILE).unwrap();
+    let config = remote::cache_root().join("example").join("packages.toml");
+    manifest::set_config_path(config.clone());
+
+    let error = import_brewfile(&brewfile).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    assert!(!config.exists());
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,100 @@
+use instant_dev::cli::{Cli, Command};
+
+#[test]
+fn test_parse_defaults_to_setup() {
+    let cli = Cli::parse(Vec::<String>::new()).unwrap();
+    assert_eq!(cli.command, Command::Setup);
+    assert!(!cli.verbose);
+}
+
+#[test]
+fn test_parse_uninstall_with_verbose() {
+    let cli = Cli::parse(["--verbose", "uninstall"]).unwrap();
+    assert_eq!(cli.command, Command::Uninstall);
+    assert!(cli.verbose);
+}
+
+#[test]
+fn test_parse_rejects_unknown_arguments() {
+    assert!(Cli::parse(["--frobnicate"]).is_err());
+    assert!(Cli::parse(["setup", "uninstall"]).is_err());
+}
+
+#[test]
+fn test_parse_dotfiles_pull() {
+    let cli = Cli::parse(["dotfiles", "pull"]).unwrap();
+    assert_eq!(cli.command, Command::DotfilesPull);
+    assert!(Cli::parse(["dotfiles"]).is_err());
+    assert!(Cli::parse(["dotfiles", "push"]).is_err());
+}
+
+#[test]
+fn test_parse_install_brew() {
+    let cli = Cli::parse(["setup", "--install-brew"]).unwrap();
+    assert_eq!(cli.command, Command::Setup);
+    assert!(cli.install_brew);
+}
+
+#[test]
+fn test_parse_brewfile_commands() {
+    let cli = Cli::parse(["import", "brewfile", "/tmp/Brewfile"]).unwrap();
+    assert_eq!(cli.command, Command::ImportBrewfile("/tmp/Brewfile".into()));
+    assert!(Cli::parse(["import", "brewfile"]).is_err());
+
+    let cli = Cli::parse(["export", "brewfile", "-v"]).unwrap();
+    assert_eq!(cli.command, Command::ExportBrewfile("Brewfile".into()));
+    assert!(cli.verbose);
+}
+
+#[test]
+fn test_parse_snapshot() {
+    let cli = Cli::parse(["snapshot"]).unwrap();
+    assert_eq!(cli.command, Command::Snapshot { diff: false });
+    let cli = Cli::parse(["snapshot", "--diff"]).unwrap();
+    assert_eq!(cli.command, Command::Snapshot { diff: true });
+}
+
+#[test]
+fn test_parse_config_show() {
+    let cli = Cli::parse(["config", "show"]).unwrap();
+    assert_eq!(cli.command, Command::ConfigShow { resolved: false });
+    let cli = Cli::parse(["config", "show", "--resolved"]).unwrap();
+    assert_eq!(cli.command, Command::ConfigShow { resolved: true });
+    assert!(Cli::parse(["config"]).is_err());
+}
+
+#[test]
+fn test_parse_config_location() {
+    let cli = Cli::parse(["--config", "git+ssh://git@host/setup.git#packages.toml"]).unwrap();
+    assert_eq!(
+        cli.config.as_deref(),
+        Some("git+ssh://git@host/setup.git#packages.toml")
+    );
+    assert_eq!(cli.command, Command::Setup);
+    assert!(Cli::parse(["--config"]).is_err());
+}
+
+#[test]
+fn test_parse_profiles() {
+    let cli = Cli::parse(["--profile", "backend,data"]).unwrap();
+    assert_eq!(
+        cli.profiles,
+        Some(vec!["backend".to_string(), "data".to_string()])
+    );
+    assert_eq!(Cli::parse(["profiles"]).unwrap().command, Command::Profiles);
+    assert_eq!(
+        Cli::parse(["profiles", "use", "data"]).unwrap().command,
+        Command::UseProfiles(vec!["data".to_string()])
+    );
+    assert_eq!(
+        Cli::parse(["profiles", "use"]).unwrap().command,
+        Command::UseProfiles(Vec::new())
+    );
+}
+
+#[test]
+fn test_parse_dry_run() {
+    assert!(Cli::parse(["--dry-run"]).unwrap().dry_run);
+    assert!(Cli::parse(["setup", "-n"]).unwrap().dry_run);
+    assert!(!Cli::parse(["setup"]).unwrap().dry_run);
+}
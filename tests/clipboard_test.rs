@@ -0,0 +1,38 @@
+use instant_dev::system::clipboard::{candidates, osc52_sequence, Clipboard};
+
+#[test]
+fn test_candidates_follow_preference_order() {
+    let result = candidates(|_| true, true);
+    assert_eq!(
+        result,
+        vec![
+            Clipboard::Pbcopy,
+            Clipboard::WlCopy,
+            Clipboard::Xclip,
+            Clipboard::Xsel,
+            Clipboard::Osc52,
+        ]
+    );
+}
+
+#[test]
+fn test_candidates_skip_missing_binaries() {
+    let result = candidates(|binary| binary == "xsel", false);
+    assert_eq!(result, vec![Clipboard::Xsel]);
+}
+
+#[test]
+fn test_candidates_empty_without_clipboard() {
+    let result = candidates(|_| false, false);
+    assert!(result.is_empty());
+}
+
+#[test]
+fn test_osc52_sequence_encodes_base64() {
+    assert_eq!(osc52_sequence("ssh"), "\x1b]52;c;c3No\x07");
+    assert_eq!(
+        osc52_sequence("ssh-ed25519"),
+        "\x1b]52;c;c3NoLWVkMjU1MTk=\x07"
+    );
+    assert_eq!(osc52_sequence("ab"), "\x1b]52;c;YWI=\x07");
+}
@@ -0,0 +1,138 @@
+use std::fs;
+
+use instant_dev::config::custom::{order, run, steps, CustomStep};
+use instant_dev::system::os::Os;
+
+mod common;
+
+fn parse(config: &str) -> Result<Vec<CustomStep>, String> {
+    steps(&toml::from_str(config).unwrap())
+}
+
+fn names(steps: &[&CustomStep]) -> Vec<String> {
+    steps.iter().map(|step| step.name.clone()).collect()
+}
+
+#[test]
+fn test_steps_from_config() {
+    let steps = parse(
+        r#"
+        [[custom]]
+        name = "acme-cli"
+        check = "command -v acme"
+        install = "./install-acme.sh"
+        depends_on = ["acme-vpn"]
+        platforms = ["macos"]
+        "#,
+    )
+    .unwrap();
+    assert_eq!(steps[0].depends_on, ["acme-vpn"]);
+    assert_eq!(steps[0].platforms, [Os::MacOs]);
+    assert!(steps[0].runs_on(Os::MacOs));
+    assert!(!steps[0].runs_on(Os::Linux));
+
+    assert!(parse("[[custom]]\nname = \"x\"\ncheck = \"true\"\n").is_err());
+    assert!(parse(
+        "[[custom]]\nname = \"x\"\ncheck = \"true\"\ninstall = \"true\"\nplatforms = [\"beos\"]\n"
+    )
+    .is_err());
+}
+
+#[test]
+fn test_steps_reject_duplicate_names() {
+    let error = parse(
+        r#"
+        [[custom]]
+        name = "acme-cli"
+        check = "true"
+        install = "true"
+
+        [[custom]]
+        name = "acme-cli"
+        check = "false"
+        install = "true"
+        "#,
+    )
+    .unwrap_err();
+    assert!(error.contains("acme-cli"), "{}", error);
+}
+
+#[test]
+fn test_order_follows_dependencies() {
+    let steps = parse(
+        r#"
+        [[custom]]
+        name = "cli"
+        check = "true"
+        install = "true"
+        depends_on = ["vpn", "certs"]
+
+        [[custom]]
+        name = "vpn"
+        check = "true"
+        install = "true"
+        depends_on = ["certs"]
+
+        [[custom]]
+        name = "certs"
+        check = "true"
+        install = "true"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(names(&order(&steps).unwrap()), ["certs", "vpn", "cli"]);
+
+    let cycle = parse(
+        "[[custom]]\nname = \"a\"\ncheck = \"true\"\ninstall = \"true\"\ndepends_on = [\"b\"]\n\
+         [[custom]]\nname = \"b\"\ncheck = \"true\"\ninstall = \"true\"\ndepends_on = [\"a\"]\n",
+    )
+    .unwrap();
+    assert!(order(&cycle).unwrap_err().contains("cycle"));
+
+    let unknown = parse(
+        "[[custom]]\nname = \"a\"\ncheck = \"true\"\ninstall = \"true\"\ndepends_on = [\"z\"]\n",
+    )
+    .unwrap();
+    assert!(order(&unknown).unwrap_err().contains("'z'"));
+}
+
+#[test]
+fn test_run_installs_when_check_fails() {
+    let dir = common::scratch("custom-run");
+    let steps = parse(
+        r#"
+        [[custom]]
+        name = "tool"
+        check = "test -f tool"
+        install = "touch tool"
+
+        [[custom]]
+        name = "broken"
+        check = "false"
+        install = "exit 1"
+
+        [[custom]]
+        name = "needs-broken"
+        check = "false"
+        install = "touch needs-broken"
+        depends_on = ["broken"]
+
+        [[custom]]
+        name = "mac-only"
+        check = "false"
+        install = "touch mac-only"
+        platforms = ["macos"]
+        "#,
+    )
+    .unwrap();
+
+    let failed = run(&steps, Os::Linux, Some(&dir)).unwrap();
+    assert_eq!(failed, ["broken", "needs-broken"]);
+    assert!(dir.join("tool").exists());
+    assert!(!dir.join("needs-broken").exists());
+    assert!(!dir.join("mac-only").exists());
+
+    assert!(run(&steps[..1], Os::Linux, Some(&dir)).unwrap().is_empty());
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,136 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use instant_dev::config::dotfiles::{backup_path, Dotfiles, LinkStatus};
+use instant_dev::system::environment::rc_content;
+use instant_dev::system::shell::Shell;
+
+mod common;
+
+/// A local repo holding `vimrc` and `zshrc` files, committed on `main`.
+fn source_repo(dir: &Path) -> PathBuf {
+    let repo = dir.join("source");
+    fs::create_dir_all(&repo).unwrap();
+    common::git(&repo, &["init", "--quiet", "--initial-branch=main"]);
+    fs::write(repo.join("vimrc"), "set number\n").unwrap();
+    fs::write(repo.join("zshrc"), "export EDITOR=nvim\n").unwrap();
+    common::git(&repo, &["add", "vimrc", "zshrc"]);
+    common::git(&repo, &["commit", "--quiet", "-m", "Add vimrc and zshrc"]);
+    repo
+}
+
+fn dotfiles(repo: &Path, checkout: &Path, target: &str, source: &str) -> Dotfiles {
+    let config: toml::Value = toml::from_str(&format!(
+        r#"
+        [dotfiles]
+        repo = "file://{}"
+        path = "{}"
+
+        [dotfiles.links]
+        "{}" = "{}"
+        "#,
+        repo.display(),
+        checkout.display(),
+        target,
+        source
+    ))
+    .unwrap();
+    Dotfiles::from_config(&config).unwrap().unwrap()
+}
+
+#[test]
+fn test_from_config_requires_repo() {
+    let config: toml::Value = toml::from_str("[dotfiles]\npath = \"~/.dotfiles\"").unwrap();
+    assert!(Dotfiles::from_config(&config).is_err());
+    assert_eq!(
+        Dotfiles::from_config(&toml::Value::Table(Default::default())),
+        Ok(None)
+    );
+}
+
+#[test]
+fn test_from_config_resolves_relative_path_against_home() {
+    let config: toml::Value =
+        toml::from_str("[dotfiles]\nrepo = \"file:///srv/dotfiles\"\npath = \"dotfiles\"").unwrap();
+    let dotfiles = Dotfiles::from_config(&config).unwrap().unwrap();
+    assert_eq!(dotfiles.path, dirs::home_dir().unwrap().join("dotfiles"));
+}
+
+#[test]
+fn test_clone_link_and_pull() {
+    let dir = common::scratch("dotfiles-sync");
+    let repo = source_repo(&dir);
+    let home = dir.join("home");
+    fs::create_dir_all(&home).unwrap();
+    fs::write(home.join(".vimrc"), "\" old\n").unwrap();
+
+    let dotfiles = dotfiles(&repo, &dir.join("checkout"), ".vimrc", "vimrc");
+    assert!(dotfiles.clone_or_pull().unwrap());
+
+    let links = dotfiles.link(&home);
+    assert_eq!(links.len(), 1);
+    let backup = home.join(".vimrc.bak");
+    assert_eq!(
+        links[0].1.as_ref().unwrap(),
+        &LinkStatus::Replaced(backup.clone())
+    );
+    assert_eq!(fs::read_to_string(backup).unwrap(), "\" old\n");
+    assert_eq!(
+        fs::read_to_string(home.join(".vimrc")).unwrap(),
+        "set number\n"
+    );
+
+    fs::write(repo.join("vimrc"), "set relativenumber\n").unwrap();
+    common::git(&repo, &["commit", "--quiet", "-am", "Switch line numbers"]);
+
+    assert!(!dotfiles.clone_or_pull().unwrap());
+    assert_eq!(
+        dotfiles.link(&home)[0].1.as_ref().unwrap(),
+        &LinkStatus::Unchanged
+    );
+    assert_eq!(
+        fs::read_to_string(home.join(".vimrc")).unwrap(),
+        "set relativenumber\n"
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_link_keeps_rc_file_with_environment_block() {
+    let dir = common::scratch("dotfiles-rc");
+    let repo = source_repo(&dir);
+    let home = dir.join("home");
+    fs::create_dir_all(&home).unwrap();
+    let rc = rc_content(
+        Shell::Zsh,
+        "alias ll='ls -l'\n",
+        Path::new("/tmp/env.sh"),
+        &[],
+    );
+    fs::write(home.join(".zshrc"), &rc).unwrap();
+
+    let dotfiles = dotfiles(&repo, &dir.join("checkout"), ".zshrc", "zshrc");
+    assert!(dotfiles.clone_or_pull().unwrap());
+
+    let links = dotfiles.link(&home);
+    assert_eq!(links.len(), 1);
+    assert!(links[0].1.is_err());
+    assert!(fs::read_link(home.join(".zshrc")).is_err());
+    assert_eq!(fs::read_to_string(home.join(".zshrc")).unwrap(), rc);
+    assert!(!home.join(".zshrc.bak").exists());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_backup_path_skips_existing_backups() {
+    let dir = common::scratch("dotfiles-backup");
+    let target = dir.join(".vimrc");
+    assert_eq!(backup_path(&target), dir.join(".vimrc.bak"));
+
+    fs::write(dir.join(".vimrc.bak"), "").unwrap();
+    assert_eq!(backup_path(&target), dir.join(".vimrc.bak.1"));
+
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,132 @@
+use std::path::Path;
+
+use instant_dev::system::environment::{rc_content, source_line, Environment};
+use instant_dev::system::managed::{replace_block, strip_block};
+use instant_dev::system::shell::Shell;
+
+fn sample() -> Environment {
+    let mut environment = Environment::default();
+    environment.add_path("/home/jane/go/bin");
+    environment.set_var("GOPRIVATE", "github.com/acme/*");
+    environment
+}
+
+#[test]
+fn test_environment_deduplicates_entries() {
+    let mut environment = sample();
+    assert!(!environment.add_path("/home/jane/go/bin"));
+    assert!(!environment.set_var("GOPRIVATE", "github.com/acme/*"));
+    assert!(environment.set_var("GOPRIVATE", "github.com/other/*"));
+    assert_eq!(environment.paths.len(), 1);
+}
+
+#[test]
+fn test_environment_round_trips_through_toml() {
+    let environment = sample();
+    assert_eq!(
+        Environment::from_value(&environment.to_value()),
+        environment
+    );
+}
+
+#[test]
+fn test_render_posix_script() {
+    assert_eq!(
+        sample().render(Shell::Zsh),
+        "# Generated by instant-dev. Do not edit; changes are overwritten.\n\
+         export GOPRIVATE=\"github.com/acme/*\"\n\
+         export PATH=\"$PATH:/home/jane/go/bin\"\n"
+    );
+}
+
+#[test]
+fn test_render_fish_script() {
+    assert_eq!(
+        sample().render(Shell::Fish),
+        "# Generated by instant-dev. Do not edit; changes are overwritten.\n\
+         set -gx GOPRIVATE \"github.com/acme/*\"\n\
+         fish_add_path --append '/home/jane/go/bin'\n"
+    );
+}
+
+#[test]
+fn test_source_line_per_shell() {
+    let script = Path::new("/home/jane/.config/instant-dev/env.sh");
+    assert_eq!(
+        source_line(Shell::Bash, script).as_deref(),
+        Some("[ -f \"/home/jane/.config/instant-dev/env.sh\" ] && . \"/home/jane/.config/instant-dev/env.sh\"")
+    );
+    assert_eq!(
+        source_line(Shell::Nushell, Path::new("/tmp/instant-dev.nu")),
+        None
+    );
+}
+
+#[test]
+fn test_rc_content_replaces_legacy_lines() {
+    let rc = "export EDITOR=nvim\n\
+              export PATH=${PATH}:`go env GOPATH`/bin\n\
+              export PATH=\"$PATH:/home/jane/go/bin\"\n";
+    let script = Path::new("/tmp/env.sh");
+
+    let installed = rc_content(Shell::Zsh, rc, script, &sample().legacy_lines(Shell::Zsh));
+    assert_eq!(
+        installed,
+        "export EDITOR=nvim\n\n\
+         # >>> instant-dev >>>\n[ -f \"/tmp/env.sh\" ] && . \"/tmp/env.sh\"\n# <<< instant-dev <<<\n"
+    );
+    assert_eq!(
+        rc_content(
+            Shell::Zsh,
+            &installed,
+            script,
+            &sample().legacy_lines(Shell::Zsh)
+        ),
+        installed
+    );
+}
+
+#[test]
+fn test_rc_content_drops_nushell_source_block() {
+    let rc = "$env.config.show_banner = false\n\n\
+              # >>> instant-dev >>>\nsource '/tmp/env.nu'\n# <<< instant-dev <<<\n";
+    assert_eq!(
+        rc_content(Shell::Nushell, rc, Path::new("/tmp/instant-dev.nu"), &[]),
+        "$env.config.show_banner = false\n"
+    );
+}
+
+#[test]
+fn test_managed_block_is_removed_cleanly() {
+    let rc = "export EDITOR=nvim\nalias ll='ls -l'\n";
+    let line = vec!["[ -f \"/tmp/env.sh\" ] && . \"/tmp/env.sh\"".to_string()];
+
+    let installed = replace_block(rc, &line);
+    assert_eq!(
+        installed,
+        "export EDITOR=nvim\nalias ll='ls -l'\n\n\
+         # >>> instant-dev >>>\n[ -f \"/tmp/env.sh\" ] && . \"/tmp/env.sh\"\n# <<< instant-dev <<<\n"
+    );
+    assert_eq!(replace_block(&installed, &line), installed);
+    assert_eq!(strip_block(&installed), rc);
+}
+
+#[test]
+fn test_render_evals_and_sources_per_shell() {
+    let mut environment = Environment::default();
+    environment.add_eval("fnm env --use-on-cd");
+    environment.add_source("/home/jane/.nvm/nvm.sh");
+
+    assert_eq!(
+        environment.render(Shell::Bash),
+        "# Generated by instant-dev. Do not edit; changes are overwritten.\n\
+         eval \"$(fnm env --use-on-cd)\"\n\
+         [ -s \"/home/jane/.nvm/nvm.sh\" ] && . \"/home/jane/.nvm/nvm.sh\"\n"
+    );
+    assert_eq!(
+        environment.render(Shell::Fish),
+        "# Generated by instant-dev. Do not edit; changes are overwritten.\n\
+         fnm env --use-on-cd | source\n\
+         # /home/jane/.nvm/nvm.sh is not supported by fish\n"
+    );
+}
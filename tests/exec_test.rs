@@ -0,0 +1,16 @@
+use instant_dev::system::exec;
+
+#[test]
+fn test_run_reports_the_last_stderr_line() {
+    assert!(exec::run(&mut exec::shell("true", None)).is_ok());
+
+    let error = exec::run(&mut exec::shell(
+        "echo first >&2; echo last >&2; exit 1",
+        None,
+    ))
+    .unwrap_err();
+    assert_eq!(error.to_string(), "last");
+
+    let error = exec::run(&mut exec::shell("exit 3", None)).unwrap_err();
+    assert!(error.to_string().contains("command exited with"));
+}
@@ -0,0 +1,215 @@
+use std::path::Path;
+
+use instant_dev::config::git::{git_profiles, public_key_path, GitProfile};
+use instant_dev::config::gitconfig::{desired_settings, set_global};
+use instant_dev::config::gitignore::{configured_patterns, merge_ignore};
+use instant_dev::config::signing::{merge_allowed_signers, Signing, SigningFormat};
+use instant_dev::system::settings::{diff_settings, SettingChange};
+
+fn parse(content: &str) -> toml::Value {
+    toml::from_str(content).expect("invalid test config")
+}
+
+#[test]
+fn test_git_profiles_from_config() {
+    let config = parse(
+        r#"
+        [git.profiles.work]
+        directory = "~/work/**"
+        user = "Jane Doe"
+        email = "jane@acme.com"
+
+        [git.profiles.personal]
+        directory = "~/code"
+        user = "Jane"
+        email = "jane@example.com"
+        ssh_key = "~/.ssh/personal"
+        "#,
+    );
+
+    let profiles = git_profiles(&config);
+    assert_eq!(profiles.len(), 2);
+
+    let work = profiles.iter().find(|p| p.name == "work").unwrap();
+    assert_eq!(work.ssh_key, "~/.ssh/id_ed25519_work");
+    assert_eq!(work.gitdir(), "~/work/**");
+
+    let personal = profiles.iter().find(|p| p.name == "personal").unwrap();
+    assert_eq!(personal.ssh_key, "~/.ssh/personal");
+    assert_eq!(personal.gitdir(), "~/code/");
+}
+
+#[test]
+fn test_git_profiles_skip_incomplete_entries() {
+    let config = parse(
+        r#"
+        [git.profiles.broken]
+        directory = "~/broken"
+        "#,
+    );
+
+    assert!(git_profiles(&config).is_empty());
+}
+
+#[test]
+fn test_profile_include_contents() {
+    let profile = GitProfile {
+        name: "work".to_string(),
+        directory: "~/work/".to_string(),
+        user: "Jane \"JD\" Doe".to_string(),
+        email: "jane@acme.com".to_string(),
+        ssh_key: "~/.ssh/id_ed25519_work".to_string(),
+    };
+
+    assert_eq!(
+        profile.include_contents(),
+        "# Generated by instant-dev for the 'work' profile.\n\
+         [user]\n\tname = \"Jane \\\"JD\\\" Doe\"\n\temail = \"jane@acme.com\"\n\
+         [core]\n\tsshCommand = \"ssh -i ~/.ssh/id_ed25519_work -o IdentitiesOnly=yes\"\n"
+    );
+}
+
+#[test]
+fn test_public_key_path_appends_pub() {
+    assert_eq!(
+        public_key_path(Path::new("/home/jane/.ssh/id_ed25519_acme.corp")),
+        Path::new("/home/jane/.ssh/id_ed25519_acme.corp.pub")
+    );
+    assert_eq!(
+        public_key_path(Path::new("/home/jane/.ssh/id_ed25519")),
+        Path::new("/home/jane/.ssh/id_ed25519.pub")
+    );
+}
+
+#[test]
+fn test_set_global_reports_git_failures() {
+    // git rejects the key before touching the global config.
+    assert!(set_global("not a valid key", "value").is_err());
+}
+
+#[test]
+fn test_signing_defaults_to_ssh_key() {
+    let config = parse("[git.signing]\nupload = true\n");
+
+    let signing = Signing::from_config(&config).unwrap().unwrap();
+    assert_eq!(signing.format, SigningFormat::Ssh);
+    assert_eq!(signing.key, "~/.ssh/id_ed25519.pub");
+    assert!(signing.upload);
+    assert!(signing.verify);
+}
+
+#[test]
+fn test_signing_gpg_requires_key() {
+    let config = parse("[git.signing]\nformat = \"gpg\"\n");
+    assert!(Signing::from_config(&config).is_err());
+
+    let config = parse("[git]\n");
+    assert_eq!(Signing::from_config(&config), Ok(None));
+}
+
+#[test]
+fn test_merge_allowed_signers_is_idempotent() {
+    let key = "ssh-ed25519 AAAAC3Nza jane@acme.com\n";
+    let existing = "bob@acme.com namespaces=\"git\" ssh-ed25519 AAAAB0b\n";
+
+    let merged = merge_allowed_signers(existing, "jane@acme.com", key);
+    assert_eq!(
+        merged,
+        "bob@acme.com namespaces=\"git\" ssh-ed25519 AAAAB0b\n\
+         jane@acme.com namespaces=\"git\" ssh-ed25519 AAAAC3Nza jane@acme.com\n"
+    );
+    assert_eq!(merge_allowed_signers(&merged, "jane@acme.com", key), merged);
+}
+
+#[test]
+fn test_desired_settings_merge_preset_config_and_aliases() {
+    let config = parse(
+        r#"
+        [git]
+        preset = "team"
+
+        [git.config]
+        "pull.rebase" = false
+        core = { editor = "nvim" }
+
+        [git.aliases]
+        st = "status -sb"
+        "#,
+    );
+
+    let settings = desired_settings(&config);
+    assert_eq!(settings.get("init.defaultBranch").unwrap(), "main");
+    assert_eq!(settings.get("pull.rebase").unwrap(), "false");
+    assert_eq!(settings.get("core.editor").unwrap(), "nvim");
+    assert_eq!(settings.get("alias.st").unwrap(), "status -sb");
+}
+
+#[test]
+fn test_diff_settings_only_reports_changes() {
+    let config = parse(
+        r#"
+        [git.config]
+        "pull.rebase" = true
+        "init.defaultBranch" = "main"
+        "core.editor" = "nvim"
+        "#,
+    );
+
+    let changes = diff_settings(&desired_settings(&config), |key| match key {
+        "pull.rebase" => Some("true".to_string()),
+        "init.defaultBranch" => Some("master".to_string()),
+        _ => None,
+    });
+
+    assert_eq!(
+        changes,
+        vec![
+            SettingChange {
+                key: "core.editor".to_string(),
+                current: None,
+                desired: "nvim".to_string(),
+            },
+            SettingChange {
+                key: "init.defaultBranch".to_string(),
+                current: Some("master".to_string()),
+                desired: "main".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_configured_patterns_expand_templates() {
+    let config = parse(
+        r#"
+        [gitignore]
+        templates = ["macos", "rust"]
+        patterns = ["target/", "*.local"]
+        "#,
+    );
+
+    assert_eq!(
+        configured_patterns(&config),
+        vec![
+            ".DS_Store",
+            ".AppleDouble",
+            ".LSOverride",
+            "._*",
+            "target/",
+            "*.local"
+        ]
+    );
+}
+
+#[test]
+fn test_merge_ignore_keeps_user_lines() {
+    let patterns = vec![".DS_Store".to_string(), ".idea/".to_string()];
+    let existing = "# mine\n.DS_Store\n*.log\n";
+
+    let merged = merge_ignore(existing, &patterns);
+    assert_eq!(
+        merged,
+        "# mine\n.DS_Store\n*.log\n\n# >>> instant-dev >>>\n.idea/\n# <<< instant-dev <<<\n"
+    );
+    assert_eq!(merge_ignore(&merged, &patterns), merged);
+}
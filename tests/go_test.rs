@@ -0,0 +1,117 @@
+use std::path::PathBuf;
+
+use instant_dev::config::go::{
+    desired_go_env, go_tools, gopath_bin, insteadof_rewrites, module_version, GoTool,
+};
+
+fn parse(content: &str) -> toml::Value {
+    toml::from_str(content).expect("invalid test config")
+}
+
+#[test]
+fn test_go_tools_default_without_config() {
+    let tools = go_tools(None);
+    let binaries: Vec<String> = tools.iter().map(GoTool::binary).collect();
+    assert_eq!(binaries, vec!["goimports", "swag"]);
+    assert!(tools.iter().all(|tool| tool.version == "latest"));
+}
+
+#[test]
+fn test_go_tools_parse_pins() {
+    let config = parse(
+        r#"
+        [go]
+        tools = [
+          "golang.org/x/tools/cmd/goimports",
+          "github.com/swaggo/swag/cmd/swag@v1.16.3",
+          { package = "github.com/go-delve/delve/cmd/dlv", version = "v1.22.1" },
+        ]
+        "#,
+    );
+
+    let tools = go_tools(Some(&config));
+    assert_eq!(
+        tools.iter().map(GoTool::install_target).collect::<Vec<_>>(),
+        vec![
+            "golang.org/x/tools/cmd/goimports@latest",
+            "github.com/swaggo/swag/cmd/swag@v1.16.3",
+            "github.com/go-delve/delve/cmd/dlv@v1.22.1",
+        ]
+    );
+}
+
+#[test]
+fn test_go_tools_empty_list_installs_nothing() {
+    let config = parse("[go]\ntools = []\n");
+    assert!(go_tools(Some(&config)).is_empty());
+}
+
+#[test]
+fn test_binary_skips_major_version_suffix() {
+    let tool = GoTool {
+        package: "github.com/golangci/golangci-lint/v2".to_string(),
+        version: "latest".to_string(),
+    };
+    assert_eq!(tool.binary(), "golangci-lint");
+}
+
+#[test]
+fn test_module_version_from_go_version_output() {
+    let output = "/home/jane/go/bin/swag: go1.22.0\n\
+                  \tpath\tgithub.com/swaggo/swag/cmd/swag\n\
+                  \tmod\tgithub.com/swaggo/swag\tv1.16.3\th1:abc=\n";
+
+    assert_eq!(module_version(output), Some("v1.16.3".to_string()));
+    assert_eq!(module_version("not a binary"), None);
+}
+
+#[test]
+fn test_desired_go_env_joins_lists() {
+    let config = parse(
+        r#"
+        [go.env]
+        GOPRIVATE = ["github.com/acme/*", "git.corp.example.com"]
+        GOPROXY = "https://proxy.corp.example.com,direct"
+        "#,
+    );
+
+    let env = desired_go_env(&config);
+    assert_eq!(
+        env.get("GOPRIVATE").unwrap(),
+        "github.com/acme/*,git.corp.example.com"
+    );
+    assert_eq!(
+        env.get("GOPROXY").unwrap(),
+        "https://proxy.corp.example.com,direct"
+    );
+}
+
+#[test]
+fn test_insteadof_rewrites_from_goprivate() {
+    let rewrites = insteadof_rewrites("github.com/acme/*,git.corp.example.com,*.internal.dev");
+
+    assert_eq!(rewrites.len(), 2);
+    assert_eq!(
+        rewrites.get("url.git@github.com:acme/.insteadOf").unwrap(),
+        "https://github.com/acme/"
+    );
+    assert_eq!(
+        rewrites
+            .get("url.git@git.corp.example.com:.insteadOf")
+            .unwrap(),
+        "https://git.corp.example.com/"
+    );
+}
+
+#[test]
+fn test_gopath_bin_uses_first_entry() {
+    assert_eq!(
+        gopath_bin("/home/jane/go:/opt/go"),
+        Some(PathBuf::from("/home/jane/go/bin"))
+    );
+    assert_eq!(
+        gopath_bin("/home/jane/go"),
+        Some(PathBuf::from("/home/jane/go/bin"))
+    );
+    assert_eq!(gopath_bin(""), None);
+}
@@ -0,0 +1,38 @@
+use std::env;
+use std::fs;
+use std::process;
+
+use instant_dev::brew::brew::{install_homebrew_from_script, sha256, verify_checksum};
+
+#[test]
+fn test_verify_checksum() {
+    let file = env::temp_dir().join(format!("instant-dev-checksum-{}", process::id()));
+    fs::write(&file, "hello\n").unwrap();
+
+    let expected = "5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03";
+    assert_eq!(sha256(&file).unwrap(), expected);
+    assert!(verify_checksum(&file, &expected.to_uppercase()).is_ok());
+    assert!(verify_checksum(&file, "0000").is_err());
+
+    fs::remove_file(file).unwrap();
+}
+
+#[test]
+fn test_install_homebrew_from_script_checks_the_download() {
+    let installer = env::temp_dir().join(format!("instant-dev-installer-{}.sh", process::id()));
+    let marker = env::temp_dir().join(format!("instant-dev-installer-ran-{}", process::id()));
+    fs::write(&installer, format!("touch '{}'\n", marker.display())).unwrap();
+    let url = format!("file://{}", installer.display());
+
+    assert!(install_homebrew_from_script(&url, Some("0000")).is_err());
+    assert!(!marker.exists());
+
+    let checksum = sha256(&installer).unwrap();
+    assert!(install_homebrew_from_script(&url, Some(&checksum)).is_ok());
+    assert!(marker.exists());
+
+    assert!(install_homebrew_from_script("file:///nonexistent/install.sh", None).is_err());
+
+    fs::remove_file(installer).unwrap();
+    fs::remove_file(marker).unwrap();
+}
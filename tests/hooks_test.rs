@@ -0,0 +1,92 @@
+use std::fs;
+
+use instant_dev::config::hooks::{Hooks, OnFailure, When};
+
+mod common;
+
+fn hooks(config: &str) -> Hooks {
+    Hooks::from_config(&toml::from_str(config).unwrap()).unwrap()
+}
+
+#[test]
+fn test_from_config() {
+    let hooks = hooks(
+        r#"
+        [hooks]
+        on_failure = "abort"
+
+        [hooks.packages]
+        post = "./install-vpn.sh"
+
+        [hooks.git]
+        pre = ["echo one", "echo two"]
+        on_failure = "warn"
+        "#,
+    );
+    assert_eq!(hooks.stages["packages"].post, ["./install-vpn.sh"]);
+    assert_eq!(hooks.stages["packages"].on_failure, OnFailure::Abort);
+    assert_eq!(hooks.stages["git"].commands(When::Pre).len(), 2);
+    assert_eq!(hooks.stages["git"].on_failure, OnFailure::Warn);
+
+    let invalid = toml::from_str("[hooks.git]\non_failure = \"ignore\"\n").unwrap();
+    assert!(Hooks::from_config(&invalid).is_err());
+}
+
+#[test]
+fn test_from_config_rejects_unknown_stages_and_settings() {
+    for config in [
+        "[hooks.casks]\npre = \"true\"\n",
+        "[hooks.pakages]\npost = \"true\"\n",
+        "[hooks]\ngit = \"true\"\n",
+        "[hooks.git]\npots = \"true\"\n",
+    ] {
+        let config = toml::from_str(config).unwrap();
+        assert!(Hooks::from_config(&config).is_err(), "{:?}", config);
+    }
+
+    let node = hooks("[hooks.node]\npre = \"true\"\n");
+    assert_eq!(node.stages["node"].pre, ["true"]);
+}
+
+#[test]
+fn test_from_config_rejects_non_string_commands() {
+    for config in [
+        "[hooks.git]\npre = [\"echo one\", 2]\n",
+        "[hooks.git]\npost = true\n",
+    ] {
+        let config = toml::from_str(config).unwrap();
+        assert!(Hooks::from_config(&config).is_err(), "{:?}", config);
+    }
+}
+
+#[test]
+fn test_run_exposes_context() {
+    let dir = common::scratch("hooks-context");
+    let hooks = hooks(
+        r#"
+        [hooks.git]
+        post = "echo \"$INSTANT_DEV_STAGE $INSTANT_DEV_HOOK $INSTANT_DEV_DRY_RUN\" > out.txt"
+        "#,
+    );
+
+    hooks.run("git", When::Pre, Some(&dir)).unwrap();
+    assert!(!dir.join("out.txt").exists());
+    hooks.run("git", When::Post, Some(&dir)).unwrap();
+    assert_eq!(
+        fs::read_to_string(dir.join("out.txt")).unwrap(),
+        "git post 0\n"
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_failed_hook_aborts_or_warns() {
+    let warn = hooks("[hooks.brew]\npre = \"exit 3\"\n");
+    assert!(warn.run("brew", When::Pre, None).is_ok());
+
+    let abort =
+        hooks("[hooks.brew]\npre = [\"exit 3\", \"echo unreachable\"]\non_failure = \"abort\"\n");
+    let error = abort.run("brew", When::Pre, None).unwrap_err();
+    assert!(error.to_string().contains("pre-brew hook `exit 3` failed"));
+}
@@ -0,0 +1,88 @@
+use std::fs;
+
+use instant_dev::config::layers::{merge, render, resolve, Sources};
+use instant_dev::config::manifest;
+use toml::Value;
+
+mod common;
+
+#[test]
+fn test_merge_unions_lists_and_removes_bang_entries() {
+    let mut base: Value = toml::from_str(
+        r#"
+        packages = ["git", "go", "lazygit"]
+        [git.config]
+        "core.editor" = "vim"
+        "pull.rebase" = "true"
+        "#,
+    )
+    .unwrap();
+    let overlay: Value = toml::from_str(
+        r#"
+        packages = ["ripgrep", "!lazygit", "go"]
+        [git.config]
+        "core.editor" = "nvim"
+        "#,
+    )
+    .unwrap();
+
+    let mut sources = Sources::new();
+    merge(&mut base, &overlay, "", "personal", &mut sources);
+
+    assert_eq!(
+        manifest::strings(&base, &["packages"]),
+        ["git", "go", "ripgrep"]
+    );
+    let git = manifest::table(&base, &["git", "config"]).unwrap();
+    assert_eq!(git["core.editor"].as_str(), Some("nvim"));
+    assert_eq!(git["pull.rebase"].as_str(), Some("true"));
+    assert_eq!(sources["git.config.core.editor"], "personal");
+    assert_eq!(sources["packages[ripgrep]"], "personal");
+    assert!(!sources.contains_key("git.config.pull.rebase"));
+}
+
+#[test]
+fn test_resolve_applies_extends_then_local_override() {
+    let dir = common::scratch("layers-resolve");
+    fs::create_dir_all(dir.join("team")).unwrap();
+    fs::write(
+        dir.join("team").join("base.toml"),
+        "packages = [\"git\", \"lazygit\"]\ncasks = [\"slack\"]\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join("packages.toml"),
+        "extends = [\"team/base.toml\"]\npackages = [\"ripgrep\"]\n",
+    )
+    .unwrap();
+    let local = dir.join("packages.local.toml");
+    fs::write(&local, "packages = [\"!lazygit\"]\ncasks = [\"!slack\"]\n").unwrap();
+
+    let resolved = resolve(&dir.join("packages.toml"), Some(&local)).unwrap();
+    assert_eq!(
+        manifest::strings(&resolved.value, &["packages"]),
+        ["git", "ripgrep"]
+    );
+    assert!(manifest::strings(&resolved.value, &["casks"]).is_empty());
+    assert!(resolved.value.get("extends").is_none());
+    assert!(resolved.sources["packages[git]"].ends_with("base.toml"));
+    assert!(resolved.sources["packages[ripgrep]"].ends_with("packages.toml"));
+
+    let shown = render(&resolved);
+    assert!(shown.contains("\"git\", # "));
+    assert!(!shown.contains("lazygit"));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_resolve_rejects_extends_cycle() {
+    let dir = common::scratch("layers-cycle");
+    fs::write(dir.join("a.toml"), "extends = [\"b.toml\"]\n").unwrap();
+    fs::write(dir.join("b.toml"), "extends = [\"a.toml\"]\n").unwrap();
+
+    let error = resolve(&dir.join("a.toml"), None).unwrap_err();
+    assert!(error.contains("extends itself"), "{}", error);
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,104 @@
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+
+use instant_dev::config::macos::{affected_apps, settings, Defaults, DefaultsValue};
+use instant_dev::system::os::Os;
+
+mod common;
+
+/// A fake `defaults` storing each value in `<dir>/store/<domain>.<key>`.
+fn fake_defaults(name: &str) -> (PathBuf, Defaults) {
+    let dir = common::scratch(&format!("macos-{}", name));
+    let store = dir.join("store");
+    fs::create_dir_all(&store).unwrap();
+
+    let binary = dir.join("defaults");
+    fs::write(
+        &binary,
+        format!(
+            "#!/bin/sh\n\
+             store='{}'\n\
+             case \"$1\" in\n\
+               read) cat \"$store/$2.$3\" 2>/dev/null || exit 1 ;;\n\
+               write) printf '%s %s\\n' \"$4\" \"$5\" > \"$store/$2.$3\" ;;\n\
+             esac\n",
+            store.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&binary, fs::Permissions::from_mode(0o755)).unwrap();
+    (dir, Defaults::new(binary))
+}
+
+fn config() -> toml::Value {
+    toml::from_str(
+        r#"
+        [macos.defaults.NSGlobalDomain]
+        KeyRepeat = 2
+
+        [macos.defaults."com.apple.dock"]
+        autohide = true
+        tilesize = 48
+        "#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_settings_are_typed() {
+    let parsed = settings(&config()).unwrap();
+    assert_eq!(parsed.len(), 3);
+    assert_eq!(parsed[0].domain, "NSGlobalDomain");
+    assert_eq!(parsed[0].value, DefaultsValue::Int(2));
+    assert_eq!(parsed[1].value.type_flag(), "-bool");
+
+    let invalid: toml::Value =
+        toml::from_str("[macos.defaults.\"com.apple.dock\"]\npersistent-apps = []").unwrap();
+    assert!(settings(&invalid).is_err());
+}
+
+#[test]
+fn test_matches_defaults_read_output() {
+    assert!(DefaultsValue::Bool(true).matches("1"));
+    assert!(DefaultsValue::Bool(false).matches("0"));
+    assert!(!DefaultsValue::Bool(true).matches("0"));
+    assert!(DefaultsValue::Float(0.5).matches("0.5"));
+    assert!(DefaultsValue::Int(48).matches("48"));
+    assert!(!DefaultsValue::String("48".to_string()).matches("48.0"));
+}
+
+#[test]
+fn test_diff_against_fake_defaults() {
+    let (dir, defaults) = fake_defaults("diff");
+    fs::write(dir.join("store/com.apple.dock.autohide"), "1\n").unwrap();
+    fs::write(dir.join("store/com.apple.dock.tilesize"), "36\n").unwrap();
+
+    let changes = defaults.diff(&settings(&config()).unwrap());
+    let keys: Vec<&str> = changes
+        .iter()
+        .map(|change| change.setting.key.as_str())
+        .collect();
+    assert_eq!(keys, vec!["KeyRepeat", "tilesize"]);
+    assert_eq!(changes[0].current, None);
+    assert_eq!(changes[1].current.as_deref(), Some("36"));
+    assert_eq!(
+        affected_apps(&changes).into_iter().collect::<Vec<_>>(),
+        vec!["Dock"]
+    );
+
+    defaults.write(&changes[1].setting).unwrap();
+    assert_eq!(
+        fs::read_to_string(dir.join("store/com.apple.dock.tilesize")).unwrap(),
+        "-int 48\n"
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_defaults_unsupported_on_linux() {
+    assert_eq!(Os::from_name("linux"), Os::Linux);
+    assert!(Defaults::for_os(Os::Linux).is_none());
+    assert!(Defaults::for_os(Os::MacOs).is_some());
+}
@@ -0,0 +1,72 @@
+use std::collections::BTreeMap;
+
+use instant_dev::config::node::{
+    global_packages, is_satisfied_by, package_name, package_version, NodeConfig, NodeManager,
+};
+
+fn parse(content: &str) -> toml::Value {
+    toml::from_str(content).expect("invalid test config")
+}
+
+#[test]
+fn test_node_config_defaults() {
+    let config = parse(
+        r#"
+        [node]
+        versions = ["20", "22"]
+        packages = ["typescript-language-server"]
+        "#,
+    );
+
+    let node = NodeConfig::from_config(&config).unwrap().unwrap();
+    assert_eq!(node.manager, NodeManager::Fnm);
+    assert_eq!(node.default, Some("20".to_string()));
+    assert!(!node.corepack);
+    assert_eq!(node.packages, vec!["typescript-language-server"]);
+}
+
+#[test]
+fn test_node_config_rejects_unknown_manager() {
+    let config = parse("[node]\nmanager = \"volta\"\n");
+    assert!(NodeConfig::from_config(&config).is_err());
+    assert_eq!(NodeConfig::from_config(&parse("[go]\n")), Ok(None));
+}
+
+#[test]
+fn test_global_packages_from_parseable_output() {
+    let output = "/home/jane/.fnm/node-versions/v22.1.0/installation/lib:\n\
+                  /home/jane/.fnm/node-versions/v22.1.0/installation/lib/node_modules/corepack:corepack@0.28.0\n\
+                  /home/jane/.fnm/node-versions/v22.1.0/installation/lib/node_modules/@fsouza/prettierd:@fsouza/prettierd@0.25.0:\n";
+
+    assert_eq!(
+        global_packages(output),
+        BTreeMap::from([
+            ("corepack".to_string(), "0.28.0".to_string()),
+            ("@fsouza/prettierd".to_string(), "0.25.0".to_string()),
+        ])
+    );
+}
+
+#[test]
+fn test_pinned_packages_need_the_pinned_version() {
+    let installed = BTreeMap::from([("typescript".to_string(), "5.4.0".to_string())]);
+
+    assert!(is_satisfied_by("typescript", &installed));
+    assert!(is_satisfied_by("typescript@5.4.0", &installed));
+    assert!(!is_satisfied_by("typescript@5.5.0", &installed));
+    assert!(is_satisfied_by("typescript@latest", &installed));
+    assert!(!is_satisfied_by("eslint", &installed));
+}
+
+#[test]
+fn test_package_name_strips_versions() {
+    assert_eq!(package_name("typescript@5.4.0"), "typescript");
+    assert_eq!(
+        package_name("@fsouza/prettierd@0.25.0"),
+        "@fsouza/prettierd"
+    );
+    assert_eq!(package_name("@fsouza/prettierd"), "@fsouza/prettierd");
+    assert_eq!(package_name(""), "");
+    assert_eq!(package_version("@fsouza/prettierd@0.25.0"), Some("0.25.0"));
+    assert_eq!(package_version("@fsouza/prettierd"), None);
+}
@@ -0,0 +1,66 @@
+use instant_dev::system::package_manager::{
+    by_name, detect, dpkg_installed, resolve_package, Homebrew,
+};
+
+fn config() -> toml::Value {
+    toml::from_str(
+        r#"
+        packages = ["go", "lazygit", "ripgrep"]
+
+        [package_map.go]
+        apt = "golang-go"
+        dnf = "golang"
+
+        [package_map.lazygit]
+        apt = false
+        "#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_resolve_package_per_manager() {
+    let config = config();
+    assert_eq!(
+        resolve_package(&config, "go", "apt").as_deref(),
+        Some("golang-go")
+    );
+    assert_eq!(
+        resolve_package(&config, "go", "brew").as_deref(),
+        Some("go")
+    );
+    assert_eq!(resolve_package(&config, "lazygit", "apt"), None);
+    assert_eq!(
+        resolve_package(&config, "ripgrep", "pacman").as_deref(),
+        Some("ripgrep")
+    );
+}
+
+#[test]
+fn test_dpkg_installed_skips_removed_packages() {
+    let output = "ii  git\nrc  old-package\nii  golang-go\n";
+    assert_eq!(dpkg_installed(output), vec!["git", "golang-go"]);
+}
+
+#[test]
+fn test_configured_manager_overrides_detection() {
+    let config: toml::Value = toml::from_str("package_manager = \"pacman\"").unwrap();
+    assert_eq!(detect(Some(&config), false).name(), "pacman");
+    assert_eq!(by_name("homebrew").unwrap().name(), "brew");
+    assert!(by_name("zypper").is_none());
+}
+
+#[test]
+fn test_homebrew_installer_settings() {
+    let config: toml::Value = toml::from_str(
+        r#"
+        [homebrew]
+        installer_sha256 = "abc123"
+        "#,
+    )
+    .unwrap();
+    let homebrew = Homebrew::from_config(Some(&config), true);
+    assert!(homebrew.install);
+    assert_eq!(homebrew.installer_sha256.as_deref(), Some("abc123"));
+    assert_eq!(homebrew.installer_url, None);
+}
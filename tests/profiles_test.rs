@@ -0,0 +1,82 @@
+use instant_dev::config::layers::{Resolved, Sources};
+use instant_dev::config::manifest;
+use instant_dev::config::profiles::{additions, apply, names, parse_list};
+use toml::Value;
+
+fn config() -> Value {
+    toml::from_str(
+        r#"
+        packages = ["git"]
+        lsp = ["taplo"]
+
+        [profiles.backend]
+        packages = ["postgresql", "git"]
+        lsp = ["gopls"]
+
+        [profiles.data]
+        casks = ["dbeaver-community"]
+
+        [profiles.data.python]
+        versions = ["3.12"]
+        tools = ["jupyterlab"]
+        "#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_parse_list() {
+    assert_eq!(parse_list("backend,data"), ["backend", "data"]);
+    assert_eq!(parse_list(" backend, data\n"), ["backend", "data"]);
+    assert!(parse_list("").is_empty());
+}
+
+#[test]
+fn test_apply_adds_selected_profiles() {
+    let mut resolved = Resolved {
+        value: config(),
+        sources: Sources::new(),
+    };
+    let unknown = apply(
+        &mut resolved,
+        &[
+            "backend".to_string(),
+            "data".to_string(),
+            "mobile".to_string(),
+        ],
+    );
+
+    assert_eq!(unknown, ["mobile"]);
+    assert_eq!(
+        manifest::strings(&resolved.value, &["packages"]),
+        ["git", "postgresql"]
+    );
+    assert_eq!(
+        manifest::strings(&resolved.value, &["lsp"]),
+        ["taplo", "gopls"]
+    );
+    assert_eq!(
+        manifest::strings(&resolved.value, &["casks"]),
+        ["dbeaver-community"]
+    );
+    assert_eq!(
+        manifest::strings(&resolved.value, &["python", "tools"]),
+        ["jupyterlab"]
+    );
+    assert_eq!(resolved.sources["packages[postgresql]"], "profile backend");
+    assert_eq!(resolved.sources["python.versions[3.12]"], "profile data");
+}
+
+#[test]
+fn test_names_and_additions() {
+    let config = config();
+    assert_eq!(names(&config), ["backend", "data"]);
+    assert_eq!(
+        additions(&config["profiles"]["data"]),
+        [
+            "casks: dbeaver-community",
+            "python.tools: jupyterlab",
+            "python.versions: 3.12",
+        ]
+    );
+}
@@ -0,0 +1,59 @@
+use instant_dev::config::python::{installed_tools, tool_name, PythonConfig, PythonManager};
+
+fn parse(content: &str) -> toml::Value {
+    toml::from_str(content).expect("invalid test config")
+}
+
+#[test]
+fn test_python_config_defaults_to_uv() {
+    let config = parse(
+        r#"
+        [python]
+        versions = ["3.12", "3.11"]
+        tools = ["ruff"]
+        "#,
+    );
+
+    let python = PythonConfig::from_config(&config).unwrap().unwrap();
+    assert_eq!(python.manager, PythonManager::Uv);
+    assert_eq!(python.default, Some("3.12".to_string()));
+    assert_eq!(python.tools, vec!["ruff"]);
+}
+
+#[test]
+fn test_python_config_pyenv_with_default() {
+    let config = parse(
+        r#"
+        [python]
+        manager = "pyenv"
+        versions = ["3.12", "3.11"]
+        default = "3.11"
+        "#,
+    );
+
+    let python = PythonConfig::from_config(&config).unwrap().unwrap();
+    assert_eq!(python.manager, PythonManager::Pyenv);
+    assert_eq!(python.default, Some("3.11".to_string()));
+    assert!(PythonConfig::from_config(&parse("[python]\nmanager = \"conda\"\n")).is_err());
+}
+
+#[test]
+fn test_installed_tools_from_uv_tool_list() {
+    let output = "httpie v3.2.2\n- http\n- https\nruff v0.4.4\n- ruff\n";
+    assert_eq!(installed_tools(output), vec!["httpie", "ruff"]);
+}
+
+#[test]
+fn test_installed_tools_from_pipx_list() {
+    let output = "black 24.4.2\nruff 0.4.4\n";
+    assert_eq!(installed_tools(output), vec!["black", "ruff"]);
+}
+
+#[test]
+fn test_tool_name_strips_version_specifiers() {
+    assert_eq!(tool_name("ruff==0.5.0"), "ruff");
+    assert_eq!(tool_name("black>=24,<25"), "black");
+    assert_eq!(tool_name("httpie[socks] ~= 3.2"), "httpie");
+    assert_eq!(tool_name("Pre_Commit"), "pre-commit");
+    assert_eq!(tool_name("ruff"), "ruff");
+}
@@ -0,0 +1,130 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use instant_dev::config::remote::{FetchStatus, RemoteConfig};
+
+mod common;
+
+/// Commit `content` as `team/packages.toml` and push it to the bare repo.
+fn publish(work: &Path, content: &str) -> String {
+    fs::create_dir_all(work.join("team")).unwrap();
+    fs::write(work.join("team").join("packages.toml"), content).unwrap();
+    common::git(work, &["add", "-A"]);
+    common::git(work, &["commit", "--quiet", "-m", "Update config"]);
+    common::git(work, &["push", "--quiet", "origin", "HEAD:main"]);
+    common::git(work, &["rev-parse", "HEAD"])
+}
+
+/// A bare repo and a clone of it to publish configs from.
+fn remote_repo(dir: &Path) -> (PathBuf, PathBuf) {
+    let bare = dir.join("setup.git");
+    let work = dir.join("work");
+    fs::create_dir_all(&bare).unwrap();
+    fs::create_dir_all(&work).unwrap();
+    common::git(
+        &bare,
+        &["init", "--quiet", "--bare", "--initial-branch=main"],
+    );
+    common::git(&work, &["init", "--quiet", "--initial-branch=main"]);
+    common::git(&work, &["remote", "add", "origin", bare.to_str().unwrap()]);
+    (bare, work)
+}
+
+#[test]
+fn test_parse_locations() {
+    assert_eq!(
+        RemoteConfig::parse("git+ssh://git@github.com/acme/setup.git?ref=v2#team/packages.toml"),
+        Ok(Some(RemoteConfig::Git {
+            url: "ssh://git@github.com/acme/setup.git".to_string(),
+            reference: Some("v2".to_string()),
+            path: "team/packages.toml".to_string(),
+        }))
+    );
+    assert_eq!(
+        RemoteConfig::parse("git+https://github.com/acme/setup.git"),
+        Ok(Some(RemoteConfig::Git {
+            url: "https://github.com/acme/setup.git".to_string(),
+            reference: None,
+            path: "packages.toml".to_string(),
+        }))
+    );
+    assert_eq!(
+        RemoteConfig::parse("https://example.com/packages.toml"),
+        Ok(Some(RemoteConfig::Https {
+            url: "https://example.com/packages.toml".to_string(),
+        }))
+    );
+    assert_eq!(RemoteConfig::parse("~/team/packages.toml"), Ok(None));
+}
+
+#[test]
+fn test_parse_rejects_plain_http() {
+    assert!(RemoteConfig::parse("http://example.com/packages.toml").is_err());
+    assert!(RemoteConfig::parse("ftp://example.com/packages.toml").is_err());
+}
+
+#[test]
+fn test_fetch_git_updates_cache_and_falls_back_offline() {
+    let dir = common::scratch("remote-git");
+    let (bare, work) = remote_repo(&dir);
+    let cache = dir.join("cache");
+    let first = publish(&work, "packages = [\"git\"]\n");
+
+    let remote = RemoteConfig::parse(&format!("git+file://{}#team/packages.toml", bare.display()))
+        .unwrap()
+        .unwrap();
+    let fetched = remote.fetch(&cache).unwrap();
+    assert_eq!(fetched.status, FetchStatus::New);
+    assert_eq!(fetched.commit.as_deref(), Some(first.as_str()));
+    assert_eq!(
+        fs::read_to_string(&fetched.path).unwrap(),
+        "packages = [\"git\"]\n"
+    );
+
+    assert_eq!(remote.fetch(&cache).unwrap().status, FetchStatus::Unchanged);
+
+    let second = publish(&work, "packages = [\"git\", \"go\"]\n");
+    let fetched = remote.fetch(&cache).unwrap();
+    assert_eq!(fetched.status, FetchStatus::Updated { from: Some(first) });
+    assert_eq!(fetched.commit.as_deref(), Some(second.as_str()));
+
+    fs::rename(&bare, dir.join("moved.git")).unwrap();
+    let fetched = remote.fetch(&cache).unwrap();
+    assert!(matches!(fetched.status, FetchStatus::Offline { .. }));
+    assert_eq!(
+        fs::read_to_string(&fetched.path).unwrap(),
+        "packages = [\"git\", \"go\"]\n"
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_fetch_git_pins_ref() {
+    let dir = common::scratch("remote-pin");
+    let (bare, work) = remote_repo(&dir);
+    let cache = dir.join("cache");
+    let first = publish(&work, "packages = [\"git\"]\n");
+    publish(&work, "packages = [\"go\"]\n");
+
+    let remote = RemoteConfig::parse(&format!(
+        "git+file://{}?ref={}#team/packages.toml",
+        bare.display(),
+        first
+    ))
+    .unwrap()
+    .unwrap();
+    let fetched = remote.fetch(&cache).unwrap();
+    assert_eq!(fetched.commit.as_deref(), Some(first.as_str()));
+    assert_eq!(
+        fs::read_to_string(&fetched.path).unwrap(),
+        "packages = [\"git\"]\n"
+    );
+
+    let missing = RemoteConfig::parse(&format!("git+file://{}", bare.display()))
+        .unwrap()
+        .unwrap();
+    assert!(missing.fetch(&cache).is_err());
+
+    let _ = fs::remove_dir_all(&dir);
+}
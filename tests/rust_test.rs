@@ -0,0 +1,79 @@
+use std::collections::BTreeMap;
+
+use instant_dev::config::rust::{installed_crates, CargoTool, RustConfig};
+
+fn parse(content: &str) -> toml::Value {
+    toml::from_str(content).expect("invalid test config")
+}
+
+#[test]
+fn test_rust_config_absent_without_section() {
+    assert_eq!(RustConfig::from_config(&parse("packages = []\n")), None);
+}
+
+#[test]
+fn test_rust_config_from_section() {
+    let config = parse(
+        r#"
+        [rust]
+        components = ["clippy", "rustfmt"]
+        targets = ["wasm32-unknown-unknown"]
+        tools = ["cargo-watch", "cargo-nextest@0.9.70", { name = "cargo-edit", version = "v0.12.2" }]
+        "#,
+    );
+
+    let rust = RustConfig::from_config(&config).unwrap();
+    assert_eq!(rust.toolchain, "stable");
+    assert_eq!(rust.components, vec!["clippy", "rustfmt"]);
+    assert_eq!(rust.targets, vec!["wasm32-unknown-unknown"]);
+    assert_eq!(
+        rust.tools,
+        vec![
+            CargoTool {
+                name: "cargo-watch".to_string(),
+                version: None,
+            },
+            CargoTool {
+                name: "cargo-nextest".to_string(),
+                version: Some("0.9.70".to_string()),
+            },
+            CargoTool {
+                name: "cargo-edit".to_string(),
+                version: Some("0.12.2".to_string()),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_installed_crates_from_cargo_install_list() {
+    let output = "cargo-nextest v0.9.70:\n    cargo-nextest\n\
+                  tokei v12.1.2 (/home/jane/src/tokei):\n    tokei\n";
+
+    let mut expected = BTreeMap::new();
+    expected.insert("cargo-nextest".to_string(), "0.9.70".to_string());
+    expected.insert("tokei".to_string(), "12.1.2".to_string());
+    assert_eq!(installed_crates(output), expected);
+}
+
+#[test]
+fn test_tool_satisfied_only_at_requested_version() {
+    let installed = installed_crates("cargo-nextest v0.9.70:\n    cargo-nextest\n");
+
+    let pinned = CargoTool {
+        name: "cargo-nextest".to_string(),
+        version: Some("0.9.71".to_string()),
+    };
+    let unpinned = CargoTool {
+        name: "cargo-nextest".to_string(),
+        version: None,
+    };
+    let missing = CargoTool {
+        name: "cargo-watch".to_string(),
+        version: None,
+    };
+
+    assert!(!pinned.is_satisfied_by(&installed));
+    assert!(unpinned.is_satisfied_by(&installed));
+    assert!(!missing.is_satisfied_by(&installed));
+}
@@ -0,0 +1,80 @@
+use std::path::Path;
+
+use instant_dev::system::shell::{shell_from_passwd, Shell};
+
+#[test]
+fn test_shell_from_path() {
+    assert_eq!(Shell::from_path("/bin/zsh"), Some(Shell::Zsh));
+    assert_eq!(Shell::from_path("/usr/local/bin/bash"), Some(Shell::Bash));
+    assert_eq!(
+        Shell::from_path("/opt/homebrew/bin/fish"),
+        Some(Shell::Fish)
+    );
+    assert_eq!(Shell::from_path("/usr/bin/nu\n"), Some(Shell::Nushell));
+    assert_eq!(Shell::from_path("/bin/tcsh"), None);
+}
+
+#[test]
+fn test_shell_from_passwd() {
+    let entries = "root:x:0:0:root:/root:/bin/bash\n\
+                   jane:x:1000:1000:Jane:/home/jane:/usr/bin/fish\n";
+
+    assert_eq!(
+        shell_from_passwd(entries, "jane"),
+        Some("/usr/bin/fish".to_string())
+    );
+    assert_eq!(shell_from_passwd(entries, "bob"), None);
+}
+
+#[test]
+fn test_path_line_per_shell() {
+    let dir = "/home/jane/go/bin";
+
+    assert_eq!(
+        Shell::Zsh.path_line(dir),
+        "export PATH=\"$PATH:/home/jane/go/bin\""
+    );
+    assert_eq!(
+        Shell::Fish.path_line(dir),
+        "fish_add_path --append '/home/jane/go/bin'"
+    );
+    assert_eq!(
+        Shell::Nushell.path_line(dir),
+        "$env.PATH = ($env.PATH | split row (char esep) | append \"/home/jane/go/bin\")"
+    );
+    assert_eq!(
+        Shell::Nushell.path_line("/home/jane/it's/bin"),
+        "$env.PATH = ($env.PATH | split row (char esep) | append \"/home/jane/it's/bin\")"
+    );
+}
+
+#[test]
+fn test_env_line_per_shell() {
+    assert_eq!(
+        Shell::Bash.env_line("GOPRIVATE", "github.com/acme/*"),
+        "export GOPRIVATE=\"github.com/acme/*\""
+    );
+    assert_eq!(
+        Shell::Fish.env_line("GOPRIVATE", "github.com/acme/*"),
+        "set -gx GOPRIVATE \"github.com/acme/*\""
+    );
+    assert_eq!(
+        Shell::Nushell.env_line("GOPRIVATE", "github.com/acme/*"),
+        "$env.GOPRIVATE = \"github.com/acme/*\""
+    );
+}
+
+#[test]
+fn test_rc_file_is_under_home() {
+    assert_eq!(
+        Shell::Fish.rc_file(Path::new("/home/jane")),
+        Path::new("/home/jane/.config/fish/conf.d/instant-dev.fish")
+    );
+    assert_eq!(
+        Shell::Zsh.rc_file(Path::new("/home/jane")),
+        Path::new("/home/jane/.zshrc")
+    );
+    assert!(Shell::Nushell
+        .rc_file(Path::new("/home/jane"))
+        .starts_with("/home/jane"));
+}
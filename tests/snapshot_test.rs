@@ -0,0 +1,78 @@
+use instant_dev::config::go::{tool_from_build_info, GoTool};
+use instant_dev::config::snapshot::{diff, git_settings, Snapshot, SnapshotDiff};
+
+#[test]
+fn test_git_settings_skip_identity() {
+    let output = "user.name=Jane Doe\n\
+                  core.editor=nvim\n\
+                  alias.st=status -sb\n\
+                  includeif.gitdir:~/work/.path=~/.config/git/work\n";
+    let (settings, aliases) = git_settings(output);
+    assert_eq!(settings.len(), 1);
+    assert_eq!(settings["core.editor"], "nvim");
+    assert_eq!(aliases["st"], "status -sb");
+}
+
+#[test]
+fn test_tool_from_build_info() {
+    let output = "/home/jane/go/bin/goimports: go1.22.0\n\
+                  \tpath\tgolang.org/x/tools/cmd/goimports\n\
+                  \tmod\tgolang.org/x/tools\tv0.20.0\th1:abc=\n";
+    assert_eq!(
+        tool_from_build_info(output),
+        Some(GoTool {
+            package: "golang.org/x/tools/cmd/goimports".to_string(),
+            version: "v0.20.0".to_string(),
+        })
+    );
+}
+
+#[test]
+fn test_diff_lists_what_the_config_lacks() {
+    let snapshot = Snapshot {
+        packages: vec!["go".to_string(), "ripgrep".to_string()],
+        go_tools: vec![GoTool {
+            package: "golang.org/x/tools/cmd/goimports".to_string(),
+            version: "v0.20.0".to_string(),
+        }],
+        git_config: [
+            ("core.editor".to_string(), "nvim".to_string()),
+            ("pull.rebase".to_string(), "true".to_string()),
+            ("init.defaultbranch".to_string(), "main".to_string()),
+        ]
+        .into_iter()
+        .collect(),
+        ..Snapshot::default()
+    };
+    let config: toml::Value = toml::from_str(
+        r#"
+        packages = ["go"]
+
+        [go]
+        tools = ["golang.org/x/tools/cmd/goimports"]
+
+        [git]
+        preset = "team"
+
+        [git.config]
+        "core.editor" = "vim"
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        diff(&snapshot.to_config(), &config),
+        vec![
+            SnapshotDiff {
+                key: "git.config.core.editor".to_string(),
+                value: "nvim".to_string(),
+                configured: Some("vim".to_string()),
+            },
+            SnapshotDiff {
+                key: "packages".to_string(),
+                value: "ripgrep".to_string(),
+                configured: None,
+            },
+        ]
+    );
+}
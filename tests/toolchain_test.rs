@@ -0,0 +1,62 @@
+use instant_dev::config::{hooks, toolchain};
+
+#[test]
+fn test_registers_go_without_config_when_installed() {
+    let names: Vec<&str> = toolchain::registered(None, true)
+        .iter()
+        .map(|toolchain| toolchain.name())
+        .collect();
+    assert_eq!(names, vec!["Go"]);
+}
+
+#[test]
+fn test_skips_go_when_missing_and_not_configured() {
+    assert!(toolchain::registered(None, false).is_empty());
+
+    let config: toml::Value = toml::from_str("[go]\ntools = []\n").unwrap();
+    let names: Vec<&str> = toolchain::registered(Some(&config), false)
+        .iter()
+        .map(|toolchain| toolchain.name())
+        .collect();
+    assert_eq!(names, vec!["Go"]);
+}
+
+#[test]
+fn test_registers_configured_toolchains_in_order() {
+    let config: toml::Value = toml::from_str(
+        r#"
+        [python]
+        manager = "uv"
+
+        [rust]
+        toolchain = "stable"
+
+        [node]
+        versions = ["22"]
+        "#,
+    )
+    .unwrap();
+
+    let names: Vec<&str> = toolchain::registered(Some(&config), true)
+        .iter()
+        .map(|toolchain| toolchain.name())
+        .collect();
+    assert_eq!(names, vec!["Go", "Rust", "Node.js", "Python"]);
+
+    for toolchain in toolchain::registered(Some(&config), true) {
+        assert!(hooks::STAGES.contains(&toolchain.section()));
+    }
+}
+
+#[test]
+fn test_skips_invalid_toolchain_sections() {
+    let config: toml::Value = toml::from_str(
+        r#"
+        [node]
+        manager = "volta"
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(toolchain::registered(Some(&config), true).len(), 1);
+}
@@ -0,0 +1,140 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use instant_dev::config::workspace::{is_github_ssh, repo_name, RepoStatus, Workspace};
+
+mod common;
+
+/// A local repo with a single commit.
+fn source_repo(dir: &Path, name: &str) -> PathBuf {
+    let repo = dir.join(name);
+    fs::create_dir_all(&repo).unwrap();
+    common::git(&repo, &["init", "--quiet"]);
+    common::git(
+        &repo,
+        &["commit", "--quiet", "--allow-empty", "-m", "Initial commit"],
+    );
+    repo
+}
+
+#[test]
+fn test_repo_name() {
+    assert_eq!(repo_name("git@github.com:acme/billing.git"), "billing");
+    assert_eq!(repo_name("https://github.com/acme/api/"), "api");
+    assert_eq!(repo_name("file:///srv/repos/web.git"), "web");
+}
+
+#[test]
+fn test_is_github_ssh() {
+    assert!(is_github_ssh("git@github.com:acme/billing.git"));
+    assert!(is_github_ssh("ssh://git@github.com/acme/billing.git"));
+    assert!(!is_github_ssh("https://github.com/acme/api"));
+    assert!(!is_github_ssh("git@gitlab.com:acme/web.git"));
+    assert!(!is_github_ssh("file:///srv/repos/web.git"));
+}
+
+#[test]
+fn test_from_config_defaults_paths_to_root() {
+    let config: toml::Value = toml::from_str(
+        r#"
+        [workspace]
+        root = "/work"
+        concurrency = 2
+
+        [[repos]]
+        url = "git@github.com:acme/billing.git"
+        post_clone = "make setup"
+
+        [[repos]]
+        url = "git@github.com:acme/api.git"
+        path = "/elsewhere/api"
+
+        [[repos]]
+        path = "/missing/url"
+        "#,
+    )
+    .unwrap();
+
+    let workspace = Workspace::from_config(&config).unwrap();
+    assert_eq!(workspace.concurrency, 2);
+    assert_eq!(workspace.repos.len(), 2);
+    assert_eq!(workspace.repos[0].path, Path::new("/work/billing"));
+    assert_eq!(workspace.repos[0].post_clone.as_deref(), Some("make setup"));
+    assert_eq!(workspace.repos[1].path, Path::new("/elsewhere/api"));
+}
+
+#[test]
+fn test_sync_clones_then_fetches() {
+    let dir = common::scratch("workspace-sync");
+    let config: toml::Value = toml::from_str(&format!(
+        r#"
+        [workspace]
+        root = "{root}"
+        concurrency = 2
+
+        [[repos]]
+        url = "file://{billing}"
+        post_clone = "touch .setup-done"
+
+        [[repos]]
+        url = "file://{api}"
+
+        [[repos]]
+        url = "file://{missing}"
+        "#,
+        root = dir.join("src").display(),
+        billing = source_repo(&dir, "billing").display(),
+        api = source_repo(&dir, "api").display(),
+        missing = dir.join("nowhere/missing").display(),
+    ))
+    .unwrap();
+    let workspace = Workspace::from_config(&config).unwrap();
+
+    let finished = AtomicUsize::new(0);
+    let results = workspace.sync(|_, _| {
+        finished.fetch_add(1, Ordering::SeqCst);
+    });
+    assert_eq!(finished.load(Ordering::SeqCst), 3);
+    assert_eq!(results[0], Ok(RepoStatus::Cloned));
+    assert_eq!(results[1], Ok(RepoStatus::Cloned));
+    assert!(results[2].is_err());
+    assert!(dir.join("src/billing/.setup-done").exists());
+
+    let results = workspace.sync(|_, _| {});
+    assert_eq!(results[0], Ok(RepoStatus::Fetched));
+    assert_eq!(results[1], Ok(RepoStatus::Fetched));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_sync_retries_failed_post_clone() {
+    let dir = common::scratch("workspace-retry");
+    let config: toml::Value = toml::from_str(&format!(
+        r#"
+        [[repos]]
+        url = "file://{billing}"
+        path = "{checkout}"
+        post_clone = "test -f {ready} && touch .setup-done"
+        "#,
+        billing = source_repo(&dir, "billing").display(),
+        checkout = dir.join("checkout").display(),
+        ready = dir.join("ready").display(),
+    ))
+    .unwrap();
+    let workspace = Workspace::from_config(&config).unwrap();
+
+    assert!(workspace.sync(|_, _| {})[0].is_err());
+    assert!(!dir.join("checkout/.setup-done").exists());
+
+    fs::write(dir.join("ready"), "").unwrap();
+    assert_eq!(workspace.sync(|_, _| {})[0], Ok(RepoStatus::Fetched));
+    assert!(dir.join("checkout/.setup-done").exists());
+
+    fs::remove_file(dir.join("checkout/.setup-done")).unwrap();
+    assert_eq!(workspace.sync(|_, _| {})[0], Ok(RepoStatus::Fetched));
+    assert!(!dir.join("checkout/.setup-done").exists());
+
+    fs::remove_dir_all(dir).unwrap();
+}